@@ -0,0 +1,94 @@
+#ifndef KERNEL_H
+#define KERNEL_H
+
+/* Generated from lib.rs by `cbindgen --config cbindgen.toml --output kernel.h lib.rs`. Do not edit. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// Why the kernel rejected its input.
+typedef enum KernelErrorKind {
+  // The call was not rejected.
+  KERNEL_ERROR_KIND_NONE = 0,
+  // The input does not parse, or names an unknown or duplicate declaration.
+  KERNEL_ERROR_KIND_PARSE = 1,
+  // A term does not typecheck, or a declaration was otherwise rejected.
+  KERNEL_ERROR_KIND_CHECK = 2,
+} KernelErrorKind;
+
+// Whether a call succeeded, and why it failed otherwise.
+typedef enum KernelStatus {
+  KERNEL_STATUS_OK = 0,
+  // The input does not parse or does not typecheck, as the error kind says.
+  // The declarations before the rejected one are kept.
+  KERNEL_STATUS_REJECTED = 1,
+  // A pointer was null or a string was not UTF-8.
+  KERNEL_STATUS_INVALID_ARGUMENT = 2,
+  // The kernel panicked.
+  KERNEL_STATUS_PANIC = 3,
+} KernelStatus;
+
+// An environment of checked declarations.
+typedef struct Kernel Kernel;
+
+// The status of a call with its output, or with an error message if it failed.
+// Free it with `kernel_result_free`.
+typedef struct KernelResult {
+  enum KernelStatus status;
+  // Owned by the caller, and freed along with the result.
+  char *text;
+  // Why the input was rejected, if the status is `Rejected`.
+  enum KernelErrorKind error_kind;
+  // The byte offset in the input at which the error was detected,
+  // if the status is `Rejected`.
+  uintptr_t error_offset;
+  // The innermost declaration that was being added when the input was rejected, or null.
+  // Owned by the caller, and freed along with the result.
+  char *error_declaration;
+} KernelResult;
+
+// A new environment with the kernel's axioms, or null if that failed.
+// Free it with `kernel_free`.
+struct Kernel *kernel_new(void);
+
+// Frees an environment.
+//
+// # Safety
+//
+// `kernel` must be null or come from `kernel_new` and not have been freed.
+void kernel_free(struct Kernel *kernel);
+
+// Adds the declarations and runs the queries in `input`, in the syntax of the kernel format.
+// On success, the text is the output of the queries, one line each.
+//
+// # Safety
+//
+// `kernel` must be null or a live environment that no other thread uses,
+// and `input` must be null or a NUL-terminated string.
+struct KernelResult kernel_add(struct Kernel *kernel, const char *input);
+
+// Checks the expression `expr`. On success, the text is its type.
+//
+// # Safety
+//
+// `kernel` must be null or a live environment that no other thread modifies,
+// and `expr` must be null or a NUL-terminated string.
+struct KernelResult kernel_type_of(const struct Kernel *kernel, const char *expr);
+
+// Frees a string of a [`KernelResult`].
+//
+// # Safety
+//
+// `text` must be null or come from a [`KernelResult`] and not have been freed.
+void kernel_string_free(char *text);
+
+// Frees the strings of a [`KernelResult`].
+//
+// # Safety
+//
+// `result` must come from a call into the kernel, and its strings must not have been freed.
+void kernel_result_free(struct KernelResult result);
+
+#endif /* KERNEL_H */
@@ -0,0 +1,191 @@
+//! A C API for the kernel, to use it as a trusted back end from other languages.
+//!
+//! `kernel.h` is generated from this file with
+//! `cbindgen --config cbindgen.toml --output kernel.h lib.rs`.
+//! Strings are NUL-terminated UTF-8. Every function catches panics,
+//! after which the environment must only be freed.
+
+/// An environment of checked declarations.
+pub struct Kernel(kernel::Kernel);
+
+/// Whether a call succeeded, and why it failed otherwise.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KernelStatus {
+    Ok = 0,
+    /// The input does not parse or does not typecheck, as the error kind says.
+    /// The declarations before the rejected one are kept.
+    Rejected = 1,
+    /// A pointer was null or a string was not UTF-8.
+    InvalidArgument = 2,
+    /// The kernel panicked.
+    Panic = 3,
+}
+
+/// Why the kernel rejected its input.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KernelErrorKind {
+    /// The call was not rejected.
+    None = 0,
+    /// The input does not parse, or names an unknown or duplicate declaration.
+    Parse = 1,
+    /// A term does not typecheck, or a declaration was otherwise rejected.
+    Check = 2,
+}
+
+/// The status of a call with its output, or with an error message if it failed.
+/// Free it with `kernel_result_free`.
+#[repr(C)]
+pub struct KernelResult {
+    pub status: KernelStatus,
+    /// Owned by the caller, and freed along with the result.
+    pub text: *mut c_char,
+    /// Why the input was rejected, if the status is `Rejected`.
+    pub error_kind: KernelErrorKind,
+    /// The byte offset in the input at which the error was detected,
+    /// if the status is `Rejected`.
+    pub error_offset: usize,
+    /// The innermost declaration that was being added when the input was rejected, or null.
+    /// Owned by the caller, and freed along with the result.
+    pub error_declaration: *mut c_char,
+}
+
+/// A new environment with the kernel's axioms, or null if that failed.
+/// Free it with `kernel_free`.
+#[no_mangle]
+pub extern "C" fn kernel_new() -> *mut Kernel {
+    match panic::catch_unwind(kernel::Kernel::new) {
+        Ok(kernel) => Box::into_raw(Box::new(Kernel(kernel))),
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+/// Frees an environment.
+///
+/// # Safety
+///
+/// `kernel` must be null or come from `kernel_new` and not have been freed.
+#[no_mangle]
+pub unsafe extern "C" fn kernel_free(kernel: *mut Kernel) {
+    if !kernel.is_null() {
+        drop(Box::from_raw(kernel));
+    }
+}
+
+/// Adds the declarations and runs the queries in `input`, in the syntax of the kernel format.
+/// On success, the text is the output of the queries, one line each.
+///
+/// # Safety
+///
+/// `kernel` must be null or a live environment that no other thread uses,
+/// and `input` must be null or a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn kernel_add(kernel: *mut Kernel, input: *const c_char) -> KernelResult {
+    call(|| {
+        let kernel = kernel.as_mut().ok_or_else(|| invalid("null environment"))?;
+        let output = kernel.0.add_detailed(str(input)?).map_err(rejected)?;
+        Ok(output.join("\n"))
+    })
+}
+
+/// Checks the expression `expr`. On success, the text is its type.
+///
+/// # Safety
+///
+/// `kernel` must be null or a live environment that no other thread modifies,
+/// and `expr` must be null or a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn kernel_type_of(
+    kernel: *const Kernel,
+    expr: *const c_char,
+) -> KernelResult {
+    call(|| {
+        let kernel = kernel.as_ref().ok_or_else(|| invalid("null environment"))?;
+        kernel.0.type_of_detailed(str(expr)?).map_err(rejected)
+    })
+}
+
+/// Frees a string of a [`KernelResult`].
+///
+/// # Safety
+///
+/// `text` must be null or come from a [`KernelResult`] and not have been freed.
+#[no_mangle]
+pub unsafe extern "C" fn kernel_string_free(text: *mut c_char) {
+    if !text.is_null() {
+        drop(CString::from_raw(text));
+    }
+}
+
+/// Frees the strings of a [`KernelResult`].
+///
+/// # Safety
+///
+/// `result` must come from a call into the kernel, and its strings must not have been freed.
+#[no_mangle]
+pub unsafe extern "C" fn kernel_result_free(result: KernelResult) {
+    kernel_string_free(result.text);
+    kernel_string_free(result.error_declaration);
+}
+
+type Error = (KernelStatus, String, Option<kernel::Error>);
+
+fn call(f: impl FnOnce() -> Result<String, Error>) -> KernelResult {
+    let (status, text, error) = match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(output)) => (KernelStatus::Ok, output, None),
+        Ok(Err(error)) => error,
+        Err(_) => (KernelStatus::Panic, "the kernel panicked".to_owned(), None),
+    };
+    let (error_kind, error_offset, error_declaration) = match error {
+        Some(error) => {
+            let kind = match error.kind {
+                kernel::ErrorKind::Parse => KernelErrorKind::Parse,
+                kernel::ErrorKind::Check => KernelErrorKind::Check,
+            };
+            let declaration = error.declaration.map_or(ptr::null_mut(), c_string);
+            (kind, error.offset, declaration)
+        }
+        None => (KernelErrorKind::None, 0, ptr::null_mut()),
+    };
+    KernelResult {
+        status,
+        text: c_string(text),
+        error_kind,
+        error_offset,
+        error_declaration,
+    }
+}
+
+fn c_string(s: String) -> *mut c_char {
+    // Inputs cannot contain NUL, but names of imported declarations might.
+    let s = CString::new(s.replace('\0', "\u{FFFD}")).unwrap();
+    s.into_raw()
+}
+
+unsafe fn str<'a>(s: *const c_char) -> Result<&'a str, Error> {
+    if s.is_null() {
+        return Err(invalid("null string"));
+    }
+    CStr::from_ptr(s)
+        .to_str()
+        .map_err(|_| invalid("string is not UTF-8"))
+}
+
+fn invalid(message: &str) -> Error {
+    (KernelStatus::InvalidArgument, message.to_owned(), None)
+}
+
+fn rejected(error: kernel::Error) -> Error {
+    (KernelStatus::Rejected, error.message.clone(), Some(error))
+}
+
+#[cfg(test)]
+mod tests;
+
+use std::ffi::c_char;
+use std::ffi::CStr;
+use std::ffi::CString;
+use std::panic;
+use std::panic::AssertUnwindSafe;
+use std::ptr;
@@ -0,0 +1,64 @@
+/* Exercises the C API against the shared library. Run it with `make test`. */
+#include "kernel.h"
+
+#include <stdio.h>
+#include <string.h>
+
+static int failures = 0;
+
+/* Checks the status of a result and, if `text` is not null, its text, then frees it. */
+static void expect(KernelResult result, KernelStatus status, const char *text) {
+    if (result.status != status || (text && strcmp(result.text, text) != 0)) {
+        fprintf(stderr, "expected %d `%s`, got %d `%s`\n", status, text ? text : "",
+                result.status, result.text);
+        failures++;
+    }
+    kernel_result_free(result);
+}
+
+/* Checks that a result was rejected with the given error, then frees it. */
+static void expect_error(KernelResult result, KernelErrorKind kind, size_t offset,
+                         const char *declaration) {
+    const char *found = result.error_declaration ? result.error_declaration : "(null)";
+    if (result.status != KERNEL_STATUS_REJECTED || result.error_kind != kind ||
+        result.error_offset != offset ||
+        strcmp(found, declaration ? declaration : "(null)") != 0) {
+        fprintf(stderr, "expected error %d at %zu in `%s`, got %d at %zu in `%s`: %s\n", kind,
+                offset, declaration ? declaration : "(null)", result.error_kind,
+                result.error_offset, found, result.text);
+        failures++;
+    }
+    kernel_result_free(result);
+}
+
+int main(void) {
+    Kernel *kernel = kernel_new();
+    if (!kernel) {
+        fprintf(stderr, "kernel_new failed\n");
+        return 1;
+    }
+
+    expect(kernel_add(kernel, "def two: Nat := Nat:add 1 1;"), KERNEL_STATUS_OK, "");
+    expect(kernel_add(kernel, "check Nat:succ two;"), KERNEL_STATUS_OK, "Nat:succ two : Nat");
+    expect(kernel_type_of(kernel, "Eq:refl (Level:s Level:0) Nat two"), KERNEL_STATUS_OK,
+           "Eq (Level:s Level:0) Nat two two");
+
+    expect(kernel_add(kernel, "def three: Nat := true;"), KERNEL_STATUS_REJECTED, NULL);
+    expect(kernel_type_of(kernel, "three"), KERNEL_STATUS_REJECTED, NULL);
+    expect(kernel_add(kernel, "def two: Bool := true;"), KERNEL_STATUS_REJECTED,
+           "duplicate definition `two`");
+    expect_error(kernel_add(kernel, "def four: Nat := Nat:succ true;"), KERNEL_ERROR_KIND_CHECK,
+                 30, "four");
+    expect_error(kernel_add(kernel, "def four: Nat := (4;"), KERNEL_ERROR_KIND_PARSE, 20, "four");
+    expect_error(kernel_type_of(kernel, "Nat:succ five"), KERNEL_ERROR_KIND_PARSE, 13, NULL);
+    expect(kernel_type_of(kernel, "\xff"), KERNEL_STATUS_INVALID_ARGUMENT, NULL);
+    expect(kernel_add(kernel, NULL), KERNEL_STATUS_INVALID_ARGUMENT, NULL);
+    expect(kernel_type_of(NULL, "two"), KERNEL_STATUS_INVALID_ARGUMENT, NULL);
+
+    kernel_free(kernel);
+    if (failures) {
+        return 1;
+    }
+    puts("ok");
+    return 0;
+}
@@ -0,0 +1,75 @@
+unsafe fn take(result: KernelResult) -> (KernelStatus, String) {
+    let (status, text, _) = take_error(result);
+    (status, text)
+}
+
+/// The status and text of a result, with its error kind, offset and declaration.
+#[allow(clippy::type_complexity)]
+unsafe fn take_error(
+    result: KernelResult,
+) -> (
+    KernelStatus,
+    String,
+    (KernelErrorKind, usize, Option<String>),
+) {
+    let string = |s: *mut c_char| CStr::from_ptr(s).to_str().unwrap().to_owned();
+    let text = string(result.text);
+    let declaration =
+        (!result.error_declaration.is_null()).then(|| string(result.error_declaration));
+    let error = (result.error_kind, result.error_offset, declaration);
+    let status = result.status;
+    kernel_result_free(result);
+    (status, text, error)
+}
+
+#[test]
+fn c_api() {
+    unsafe {
+        let kernel = kernel_new();
+        assert!(!kernel.is_null());
+        let ok = |text: &str| (KernelStatus::Ok, text.to_owned());
+        let add = |input: &CStr| take(kernel_add(kernel, input.as_ptr()));
+        let type_of = |expr: &CStr| take(kernel_type_of(kernel, expr.as_ptr()));
+
+        assert_eq!(add(c"def two: Nat := Nat:add 1 1;"), ok(""));
+        assert_eq!(add(c"check two; check true;"), ok("two : Nat\ntrue : Bool"));
+        assert_eq!(type_of(c"Nat:succ two"), ok("Nat"));
+
+        let (status, message) = add(c"def three: Nat := true; def four: Nat := 4;");
+        assert_eq!(status, KernelStatus::Rejected);
+        assert!(message.contains("type mismatch"), "{message}");
+        assert_eq!(type_of(c"four").0, KernelStatus::Rejected);
+
+        let input = c"def five: Nat := 5; def six: Nat := Nat:succ (Nat:succ true); check six;";
+        let (status, _, error) = take_error(kernel_add(kernel, input.as_ptr()));
+        assert_eq!(status, KernelStatus::Rejected);
+        // `six` is checked once its value is parsed, before the `;` at 60.
+        assert_eq!(error, (KernelErrorKind::Check, 60, Some("six".to_owned())));
+        let (_, _, error) = take_error(kernel_add(kernel, c"def seven: Nat := (8;".as_ptr()));
+        assert_eq!(
+            error,
+            (KernelErrorKind::Parse, 21, Some("seven".to_owned()))
+        );
+        let (_, _, error) = take_error(kernel_type_of(kernel, c"Nat:succ eight".as_ptr()));
+        assert_eq!(error, (KernelErrorKind::Parse, 14, None));
+        let (_, _, error) = take_error(kernel_add(kernel, c"check five;".as_ptr()));
+        assert_eq!(error, (KernelErrorKind::None, 0, None));
+
+        assert_eq!(add(c"def \xff: Nat := 0;").0, KernelStatus::InvalidArgument);
+        assert_eq!(
+            take(kernel_add(kernel, ptr::null())).0,
+            KernelStatus::InvalidArgument
+        );
+        let (status, _) = take(kernel_type_of(ptr::null(), c"two".as_ptr()));
+        assert_eq!(status, KernelStatus::InvalidArgument);
+
+        kernel_free(kernel);
+        kernel_free(ptr::null_mut());
+        kernel_string_free(ptr::null_mut());
+    }
+}
+
+use crate::*;
+use std::ffi::c_char;
+use std::ffi::CStr;
+use std::ptr;
@@ -6,7 +6,7 @@ fn main() -> process::ExitCode {
         .init();
 
     match try_main() {
-        Ok(()) => process::ExitCode::SUCCESS,
+        Ok(code) => code,
         Err(e) => {
             println!("Error: {e}");
             process::ExitCode::FAILURE
@@ -14,8 +14,33 @@ fn main() -> process::ExitCode {
     }
 }
 
-fn try_main() -> io::Result<()> {
-    let mut args = env::args_os().skip(1);
+/// The exit code is 2 if some declarations depend on `sorry`.
+fn try_main() -> io::Result<process::ExitCode> {
+    let mut args = env::args_os().skip(1).peekable();
+    let (mut jobs, mut import, mut export, mut lean) = (None, None, None, None);
+    let mut dedukti = None;
+    let (mut paranoid, mut fingerprint, mut stats) = (false, false, false);
+    while let Some(flag) = args.next_if(|arg| arg.to_str().is_some_and(|a| a.starts_with("--"))) {
+        let flag = flag.to_str().unwrap();
+        let mut value = || {
+            let value = args.next();
+            value.ok_or_else(|| io::Error::other(format!("missing value for `{flag}`")))
+        };
+        match flag {
+            "--jobs" => {
+                let n = value()?.to_str().and_then(|n| n.parse::<usize>().ok());
+                jobs = Some(n.ok_or_else(|| io::Error::other("invalid job count"))?);
+            }
+            "--import" => import = Some(value()?),
+            "--export" => export = Some(value()?),
+            "--lean" => lean = Some(value()?),
+            "--dedukti" => dedukti = Some(value()?),
+            "--paranoid" => paranoid = true,
+            "--fingerprint" => fingerprint = true,
+            "--stats" => stats = true,
+            flag => return Err(io::Error::other(format!("unknown flag `{flag}`"))),
+        }
+    }
     let Some(program) = args.next() else {
         return Err(io::Error::other("missing arguments"));
     };
@@ -24,7 +49,17 @@ fn try_main() -> io::Result<()> {
         .stdout(process::Stdio::piped())
         .spawn()?;
 
-    let mut kernel = kernel::Kernel::new();
+    let mut kernel = match import {
+        Some(path) => {
+            kernel::Kernel::import(&fs::read(path)?, paranoid).map_err(io::Error::other)?
+        }
+        None => kernel::Kernel::new(),
+    };
+    if let Some(path) = lean {
+        let report = kernel.import_lean(&fs::read_to_string(path)?);
+        let report = report.map_err(io::Error::other)?;
+        report.iter().for_each(|line| println!("{line}"));
+    }
     let mut pipe = child.stdout.take().unwrap();
 
     let mut message_len = [0_u8; 4];
@@ -39,15 +74,78 @@ fn try_main() -> io::Result<()> {
         message.resize(message_len, 0);
         pipe.read_exact(&mut message)?;
         let message = str::from_utf8(&message).map_err(io::Error::other)?;
-        if let Err(e) = kernel.add(message) {
-            println!("{e}");
+        let res = match jobs {
+            Some(jobs) => kernel.add_parallel(message, jobs),
+            None => kernel.add(message),
+        };
+        match res {
+            Ok(output) => output.iter().for_each(|line| println!("{line}")),
+            Err(e) => println!("{e}"),
         }
     }
-    Ok(())
+    if fingerprint {
+        for (name, hash) in kernel.fingerprints() {
+            println!("{} {name}", hex(&hash));
+        }
+        println!("{} (environment)", hex(&kernel.fingerprint()));
+    }
+    if stats {
+        report(&kernel);
+    }
+    let sorries: Vec<_> = kernel.sorries().collect();
+    if !sorries.is_empty() {
+        println!("declarations that depend on sorry: {}", sorries.join(", "));
+    }
+    if let Some(path) = export {
+        fs::write(path, kernel.export())?;
+    }
+    if let Some(path) = dedukti {
+        fs::write(path, kernel.export_dedukti().map_err(io::Error::other)?)?;
+    }
+    Ok(match sorries.is_empty() {
+        true => process::ExitCode::SUCCESS,
+        false => process::ExitCode::from(2),
+    })
+}
+
+/// Prints the kernel's counters and the declarations that took longest to check.
+fn report(kernel: &kernel::Kernel) {
+    let stats = kernel.stats();
+    let type_of_calls = stats.type_of_hits + stats.type_of_misses;
+    let type_of_rate = 100.0 * stats.type_of_hit_rate();
+    let def_eq_rate = 100.0 * stats.def_eq_hit_rate();
+    println!("type_of: {type_of_calls} cached calls, {type_of_rate:.1}% hits");
+    println!(
+        "def_eq: {} calls, {def_eq_rate:.1}% hits",
+        stats.def_eq_calls
+    );
+    println!("whnf: {} calls", stats.whnf_calls);
+    println!("β-reductions: {}", stats.beta_reductions);
+    println!("uip attempts: {}", stats.uip_attempts);
+    let mean_vars = stats.mean_level_vars();
+    println!(
+        "level solver: {} calls, {mean_vars:.2} variables on average",
+        stats.level_solves
+    );
+    println!("max depth: {}", stats.max_depth);
+
+    let mut times = kernel.times();
+    let total: time::Duration = times.iter().map(|(_, time)| *time).sum();
+    println!("checked {} declarations in {total:.3?}", times.len());
+    times.sort_by(|(_, a), (_, b)| b.cmp(a));
+    for (name, time) in times.iter().take(10) {
+        println!("{time:>12.3?} {name}");
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
 }
 
 use std::env;
+use std::fs;
 use std::io;
 use std::io::Read as _;
 use std::process;
 use std::str;
+use std::time;
@@ -0,0 +1,591 @@
+//! Typing derivations recorded by the kernel, and a checker that replays them.
+//!
+//! A derivation is a tree of judgments, each made by a rule from its premises.
+//! Every call to `type_of` and `def_eq` made while checking an expression becomes a node,
+//! including the comparisons that failed before another branch of `def_eq` succeeded.
+//! Failed comparisons prove nothing, so the checker only verifies the nodes that claim
+//! a type or a successful comparison.
+//!
+//! The checker shares no decision procedure with the kernel. It reduces with its own
+//! computation rules for the constants the kernel recognizes, and does not run extensions,
+//! so certificates that rely on them are rejected. It decides the `level` rule by evaluating
+//! both levels at enough assignments of their atoms, like the reference checker.
+//!
+//! In text, a certificate is `(certificate <environment fingerprint> <node>)`, where a node is
+//! `(type <rule> <expr> <type> <premise>*)` or `(defeq <rule> <lhs> <rhs> <result> <premise>*)`
+//! and expressions are `(c <n>)`, `(v <n>)`, `(ω <n>)`, `(n <literal>)`, `(λ <type> <body>)`,
+//! `(Π <type> <body>)` or `(@ <function> <argument>)`.
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Node {
+    pub rule: Rule,
+    pub judgment: Judgment,
+    pub premises: Vec<Node>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Judgment {
+    /// An expression has a type.
+    Type(Expr, Expr),
+    /// Two expressions were compared, with the given result.
+    DefEq(Expr, Expr, bool),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Rule {
+    Const,
+    Var,
+    Sortω,
+    /// Typing of `Nat` literals.
+    Lit,
+    /// Typing or congruence of abstractions.
+    Lam,
+    /// Typing or congruence of Π types.
+    Pi,
+    /// Typing or congruence of applications.
+    App,
+    /// Two identical constants, variables or `Sortω`s.
+    Atom,
+    /// A literal `n + 1` and `Nat:succ` applied to `n`.
+    Succ,
+    /// The η rule for pairs.
+    Eta,
+    /// The level solver.
+    Level,
+    /// Proof irrelevance.
+    Uip,
+    Mismatch,
+}
+
+const RULES: [(Rule, &str); 13] = [
+    (Rule::Const, "const"),
+    (Rule::Var, "var"),
+    (Rule::Sortω, "sortω"),
+    (Rule::Lit, "lit"),
+    (Rule::Lam, "lam"),
+    (Rule::Pi, "pi"),
+    (Rule::App, "app"),
+    (Rule::Atom, "atom"),
+    (Rule::Succ, "succ"),
+    (Rule::Eta, "eta"),
+    (Rule::Level, "level"),
+    (Rule::Uip, "uip"),
+    (Rule::Mismatch, "mismatch"),
+];
+
+/// Checks a derivation whose root types a closed expression, returning the expression and its type.
+pub(crate) fn check(kernel: &kernel::State, node: &Node) -> Result<(Expr, Expr), String> {
+    let Judgment::Type(e, r#type) = &node.judgment else {
+        return Err("the root of a derivation must be a typing judgment".to_owned());
+    };
+    let mut checker = Checker {
+        kernel,
+        bvars: Vec::new(),
+    };
+    checker.node(node)?;
+    Ok((e.clone(), r#type.clone()))
+}
+
+struct Checker<'k> {
+    kernel: &'k kernel::State,
+    /// The types of the enclosing binders, outermost first.
+    bvars: Vec<Expr>,
+}
+
+impl Checker<'_> {
+    fn node(&mut self, node: &Node) -> Result<(), String> {
+        let premise = |i: usize| {
+            let premise = node.premises.get(i);
+            premise.ok_or_else(|| format!("`{:?}` is missing premise {i}", node.rule))
+        };
+        match (&node.judgment, node.rule) {
+            (Judgment::DefEq(.., false), _) => Ok(()),
+            (Judgment::Type(Expr::FVar(n), r#type), Rule::Const) => {
+                let def = (*n < self.kernel.len()).then(|| self.kernel.def(*n));
+                let def = def.ok_or_else(|| format!("unknown constant {n}"))?;
+                expect(&def.r#type == r#type, "constant")
+            }
+            (Judgment::Type(Expr::BVar(n), r#type), Rule::Var) => {
+                let n = *n;
+                let i = self.bvars.len().checked_sub(1 + usize::from(n));
+                let mut bvar = self.bvars[i.ok_or("unbound variable")?].clone();
+                bvar.raise(0, n + 1);
+                expect(&bvar == r#type, "variable")
+            }
+            (Judgment::Type(Expr::Sortω(n), r#type), Rule::Sortω) => expect(
+                n.checked_add(1).map(Expr::Sortω).as_ref() == Some(r#type),
+                "Sortω",
+            ),
+            (Judgment::Type(Expr::Nat(_), r#type), Rule::Lit) => expect(
+                self.kernel.natives().nat().as_ref() == Ok(r#type),
+                "literal",
+            ),
+            (Judgment::Type(Expr::Lam(l, r), r#type), Rule::Lam) => {
+                self.binder_type(premise(0)?, l)?;
+                let body_type = self.under(l, |c| c.typed(premise(1)?, r))?;
+                expect(*r#type == body_type.clone().pi(l.clone()), "abstraction")
+            }
+            (Judgment::Type(Expr::Pi(l, r), r#type), Rule::Pi) => {
+                let l_univ = self.binder_type(premise(0)?, l)?;
+                let r_univ = self.under(l, |c| c.typed(premise(1)?, r))?;
+                expect(*r#type == pi_univ(&l_univ, &r_univ)?, "Π type")
+            }
+            (Judgment::Type(Expr::App(f, a), r#type), Rule::App) => {
+                let f_type = self.typed(premise(0)?, f)?;
+                let f_type = self.whnf(&f_type);
+                let a_type = self.typed(premise(1)?, a)?;
+                let Expr::Pi(f_in, mut f_out) = f_type else {
+                    return Err("application of a non-function".to_owned());
+                };
+                self.def_eq(premise(2)?, &f_in, &a_type)?;
+                f_out.subst(a);
+                expect(*r#type == *f_out, "application")
+            }
+            (Judgment::DefEq(lhs, rhs, true), rule) => match (self.whnf(lhs), self.whnf(rhs), rule)
+            {
+                (Expr::Pi(a, b), Expr::Pi(c, d), Rule::Pi)
+                | (Expr::Lam(a, b), Expr::Lam(c, d), Rule::Lam) => {
+                    self.def_eq(premise(0)?, &a, &c)?;
+                    self.binder_type(premise(1)?, &a)?;
+                    self.under(&a, |c| c.def_eq(premise(2)?, &b, &d))
+                }
+                (Expr::App(a, b), Expr::App(c, d), Rule::App) => {
+                    self.def_eq(premise(0)?, &a, &c)?;
+                    self.def_eq(premise(1)?, &b, &d)
+                }
+                (lhs, rhs, Rule::Atom) => {
+                    let atom = matches!(
+                        lhs,
+                        Expr::FVar(_) | Expr::BVar(_) | Expr::Sortω(_) | Expr::Nat(_)
+                    );
+                    expect(atom && lhs == rhs, "atom")
+                }
+                (Expr::Nat(n), mut e, Rule::Succ) | (mut e, Expr::Nat(n), Rule::Succ) => {
+                    let natives = self.kernel.natives();
+                    let x = natives.succ_of(&mut e).filter(|_| !n.is_zero());
+                    let x = x.ok_or("invalid succ judgment")?;
+                    self.def_eq(premise(0)?, &Expr::Nat(n.sub(&Nat::from(1))), x)
+                }
+                (lhs, rhs, Rule::Level) => expect(self.level_eq(&lhs, &rhs), "level"),
+                (lhs, rhs, Rule::Eta) => {
+                    let natives = self.kernel.natives();
+                    let [sigma, _, fst, snd] = natives.sigma().ok_or("`eta` without `Sigma`")?;
+                    let (pair, t) = match (natives.pair_of(&lhs), natives.pair_of(&rhs)) {
+                        (Some(pair), None) => (pair, &rhs),
+                        (None, Some(pair)) => (pair, &lhs),
+                        _ => return Err("`eta` needs exactly one pair".to_owned()),
+                    };
+                    let [u, alpha, beta, a, b] = pair;
+                    let params = [u, alpha, beta];
+                    let project =
+                        |n: u32| Expr::FVar(n).app(params.into_iter().chain([t]).cloned());
+                    // Like `uip`, after any failed attempts.
+                    let start = node.premises.len().checked_sub(4);
+                    let premise = |i: usize| premise(start.ok_or("missing premises")? + i);
+                    let t_type = self.typed(premise(0)?, t)?;
+                    let r#type = Expr::FVar(sigma).app(params.into_iter().cloned());
+                    self.def_eq(premise(1)?, &t_type, &r#type)?;
+                    self.def_eq(premise(2)?, a, &project(fst))?;
+                    self.def_eq(premise(3)?, b, &project(snd))
+                }
+                (lhs, rhs, Rule::Uip) => {
+                    // The proof irrelevance check is made last, after any failed attempts.
+                    let start = node.premises.len().checked_sub(5);
+                    let premise = |i: usize| premise(start.ok_or("missing premises")? + i);
+                    let lhs_type = self.typed(premise(0)?, &lhs)?;
+                    let level = match self.typed(premise(1)?, &lhs_type)? {
+                        Expr::App(sort, level) if *sort == SORT => *level,
+                        _ => return Err("`uip` on a non-proof".to_owned()),
+                    };
+                    self.def_eq(premise(2)?, &level, &LEVEL_Z)?;
+                    let rhs_type = self.typed(premise(3)?, &rhs)?;
+                    self.def_eq(premise(4)?, &lhs_type, &rhs_type)
+                }
+                _ => Err(format!("`{rule:?}` does not apply to this comparison")),
+            },
+            (_, rule) => Err(format!("`{rule:?}` does not apply to this judgment")),
+        }
+    }
+    /// Checks that `node` types `e`, returning the type.
+    fn typed(&mut self, node: &Node, e: &Expr) -> Result<Expr, String> {
+        match &node.judgment {
+            Judgment::Type(expr, r#type) if expr == e => {
+                self.node(node)?;
+                Ok(r#type.clone())
+            }
+            _ => Err("expected a typing judgment of another expression".to_owned()),
+        }
+    }
+    /// Checks that `node` types `e` with a sort, returning the sort.
+    fn binder_type(&mut self, node: &Node, e: &Expr) -> Result<Expr, String> {
+        let univ = self.typed(node, e)?;
+        Univ::of(&univ)?;
+        Ok(univ)
+    }
+    /// Checks that `node` proves `lhs` and `rhs` definitionally equal.
+    fn def_eq(&mut self, node: &Node, lhs: &Expr, rhs: &Expr) -> Result<(), String> {
+        match &node.judgment {
+            Judgment::DefEq(l, r, true) if l == lhs && r == rhs => self.node(node),
+            _ => Err("expected a successful comparison of other expressions".to_owned()),
+        }
+    }
+    /// Reduces to weak head normal form with β-reduction and the computation rules
+    /// of `Nat`, `Quot:lift` and the projections of `Sigma`.
+    fn whnf(&self, e: &Expr) -> Expr {
+        let e = match e {
+            Expr::App(f, a) => match self.whnf(f) {
+                Expr::Lam(_, mut body) => {
+                    body.subst(a);
+                    return self.whnf(&body);
+                }
+                f => Expr::App(f.into(), a.clone()),
+            },
+            e => e.clone(),
+        };
+        match self.compute(&e) {
+            Some(e) => self.whnf(&e),
+            None => e,
+        }
+    }
+    /// Applies a computation rule to `e`, whose head is not an abstraction.
+    fn compute(&self, e: &Expr) -> Option<Expr> {
+        let (head, args) = spine(e);
+        let &Expr::FVar(head) = head else {
+            return None;
+        };
+        let natives = self.kernel.natives();
+        let is = |name: &str| natives.constant(name) == Some(head);
+        let literal = |e: &Expr| match self.whnf(e) {
+            Expr::Nat(n) => Some(n),
+            _ => None,
+        };
+        let applied = |head: &str, e: &Expr, arity: usize| {
+            let e = self.whnf(e);
+            let (f, args) = spine(&e);
+            let args = (is_constant(natives, f, head) && args.len() == arity).then_some(args)?;
+            Some(args.into_iter().cloned().collect::<Vec<_>>())
+        };
+        let operations = [
+            "Nat:add", "Nat:sub", "Nat:mul", "Nat:div", "Nat:mod", "Nat:beq", "Nat:ble",
+        ];
+        match args[..] {
+            [] if is("Nat:zero") => Some(Expr::Nat(Nat::default())),
+            [x] if is("Nat:succ") => Some(Expr::Nat(literal(x)?.add(&Nat::from(1)))),
+            [x, y] if operations.iter().any(|op| is(op)) => {
+                let (x, y) = (literal(x)?, literal(y)?);
+                let bool = |b: bool| natives.constant(if b { "true" } else { "false" });
+                Some(match () {
+                    _ if is("Nat:add") => Expr::Nat(x.add(&y)),
+                    _ if is("Nat:sub") => Expr::Nat(x.sub(&y)),
+                    _ if is("Nat:mul") => Expr::Nat(x.mul(&y)),
+                    _ if is("Nat:div") => Expr::Nat(x.div_rem(&y).0),
+                    _ if is("Nat:mod") => Expr::Nat(x.div_rem(&y).1),
+                    _ if is("Nat:beq") => Expr::FVar(bool(x == y)?),
+                    _ => Expr::FVar(bool(x <= y)?),
+                })
+            }
+            // `Quot:lift u v α r β f h (Quot:mk u α r a)` is `f a`.
+            [_, _, _, _, _, f, _, q, ref rest @ ..] if is("Quot:lift") => {
+                let [.., a] = <[Expr; 4]>::try_from(applied("Quot:mk", q, 4)?).ok()?;
+                Some(
+                    f.clone()
+                        .app([a].into_iter().chain(rest.iter().map(|&e| e.clone()))),
+                )
+            }
+            // `Sigma:fst u α β (Sigma:mk u α β a b)` is `a`, and `Sigma:snd` of it is `b`.
+            [_, _, _, t, ref rest @ ..] if is("Sigma:fst") || is("Sigma:snd") => {
+                let [.., a, b] = <[Expr; 5]>::try_from(applied("Sigma:mk", t, 5)?).ok()?;
+                let field = if is("Sigma:fst") { a } else { b };
+                Some(field.app(rest.iter().map(|&e| e.clone())))
+            }
+            _ => None,
+        }
+    }
+    /// Whether `lhs` and `rhs` are equal levels, at least one of which is built
+    /// from `Level:0`, `Level:s`, `Level:max` and `Level:imax`.
+    fn level_eq(&self, lhs: &Expr, rhs: &Expr) -> bool {
+        let mut atoms = Vec::new();
+        let (l, l_is_level) = self.level(lhs, &mut atoms);
+        let (r, r_is_level) = self.level(rhs, &mut atoms);
+        // Like the kernel, give up beyond 16 atoms.
+        if !(l_is_level || r_is_level) || atoms.len() > 16 {
+            return false;
+        }
+        // Both sides are equal everywhere iff they are equal when every atom is 0 or 1,
+        // and when one of the atoms that are 1 is instead larger than any offset.
+        let large = l.succs() + r.succs() + 2;
+        (0..1_u32 << atoms.len()).all(|zeros| {
+            let ones: Vec<_> = (0..atoms.len())
+                .map(|i| u64::from(zeros >> i & 1 == 0))
+                .collect();
+            let nonzero = (0..atoms.len()).filter(|&i| ones[i] == 1);
+            let mut points = [ones.clone()].into_iter().chain(nonzero.map(|i| {
+                let mut point = ones.clone();
+                point[i] = large;
+                point
+            }));
+            points.all(|point| l.eval(&point) == r.eval(&point))
+        })
+    }
+    /// The level `e` denotes, with the subterms that are not levels as atoms,
+    /// and whether `e` itself is not an atom.
+    fn level(&self, e: &Expr, atoms: &mut Vec<Expr>) -> (Level, bool) {
+        let e = self.whnf(e);
+        let mut level = |e: &Expr| Box::new(self.level(e, atoms).0);
+        let level = match spine(&e) {
+            (&LEVEL_Z, args) if args.is_empty() => Level::Zero,
+            (&LEVEL_S, args) if args.len() == 1 => Level::Succ(level(args[0])),
+            (&LEVEL_MAX, args) if args.len() == 2 => Level::Max(level(args[0]), level(args[1])),
+            (&LEVEL_IMAX, args) if args.len() == 2 => Level::IMax(level(args[0]), level(args[1])),
+            _ => {
+                let i = atoms.iter().position(|atom| *atom == e);
+                let i = i.unwrap_or_else(|| (atoms.push(e), atoms.len() - 1).1);
+                return (Level::Atom(i), false);
+            }
+        };
+        (level, true)
+    }
+    fn under<R>(&mut self, r#type: &Expr, f: impl FnOnce(&mut Self) -> R) -> R {
+        self.bvars.push(r#type.clone());
+        let res = f(self);
+        self.bvars.pop();
+        res
+    }
+}
+
+/// A level, with the subterms that are not levels replaced by atoms.
+enum Level {
+    Zero,
+    Succ(Box<Level>),
+    Max(Box<Level>, Box<Level>),
+    IMax(Box<Level>, Box<Level>),
+    Atom(usize),
+}
+
+impl Level {
+    fn eval(&self, atoms: &[u64]) -> u64 {
+        match self {
+            Self::Zero => 0,
+            Self::Succ(l) => l.eval(atoms) + 1,
+            Self::Max(a, b) => Ord::max(a.eval(atoms), b.eval(atoms)),
+            Self::IMax(a, b) => match b.eval(atoms) {
+                0 => 0,
+                b => Ord::max(a.eval(atoms), b),
+            },
+            &Self::Atom(i) => atoms[i],
+        }
+    }
+    /// The number of `Level:s` in the level, an upper bound on the offset of any atom.
+    fn succs(&self) -> u64 {
+        match self {
+            Self::Zero | Self::Atom(_) => 0,
+            Self::Succ(l) => l.succs() + 1,
+            Self::Max(a, b) | Self::IMax(a, b) => a.succs() + b.succs(),
+        }
+    }
+}
+
+/// The head of `e` and the arguments it is applied to.
+fn spine(mut e: &Expr) -> (&Expr, Vec<&Expr>) {
+    let mut args = Vec::new();
+    while let Expr::App(f, a) = e {
+        args.push(&**a);
+        e = f;
+    }
+    args.reverse();
+    (e, args)
+}
+
+fn is_constant(natives: &Natives, e: &Expr, name: &str) -> bool {
+    matches!((e, natives.constant(name)), (&Expr::FVar(n), Some(c)) if n == c)
+}
+
+fn expect(ok: bool, rule: &str) -> Result<(), String> {
+    match ok {
+        true => Ok(()),
+        false => Err(format!("invalid {rule} judgment")),
+    }
+}
+
+enum Univ<'a> {
+    Sort(&'a Expr),
+    Sortω(u16),
+}
+
+impl<'a> Univ<'a> {
+    fn of(e: &'a Expr) -> Result<Self, String> {
+        match e {
+            Expr::App(l, r) if **l == SORT => Ok(Self::Sort(r)),
+            &Expr::Sortω(n) => Ok(Self::Sortω(n)),
+            _ => Err("expected a sort".to_owned()),
+        }
+    }
+}
+
+/// The sort of a Π type whose domain and codomain live in the given sorts.
+fn pi_univ(l: &Expr, r: &Expr) -> Result<Expr, String> {
+    Ok(match (Univ::of(l)?, Univ::of(r)?) {
+        (Univ::Sort(l), Univ::Sort(r)) => {
+            let mut r = r.clone();
+            match r.lower(0, 1) {
+                Ok(()) => SORT.app([LEVEL_IMAX.app([l.clone(), r])]),
+                Err(()) => Expr::Sortω(0),
+            }
+        }
+        (Univ::Sortω(n), Univ::Sort(_)) | (Univ::Sort(_), Univ::Sortω(n)) => Expr::Sortω(n),
+        (Univ::Sortω(n), Univ::Sortω(m)) => Expr::Sortω(n.max(m)),
+    })
+}
+
+pub(crate) fn write(kernel: &kernel::State, node: &Node) -> String {
+    let mut out = format!("(certificate {}", fingerprint(kernel));
+    write_node(&mut out, node, 1);
+    out.push(')');
+    out
+}
+
+fn write_node(out: &mut String, node: &Node, indent: usize) {
+    let _ = write!(out, "\n{:indent$}(", "", indent = 2 * indent);
+    let rule = RULES.iter().find(|(r, _)| *r == node.rule).unwrap().1;
+    match &node.judgment {
+        Judgment::Type(e, r#type) => {
+            let _ = write!(out, "type {rule} {} {}", Sexp(e), Sexp(r#type));
+        }
+        Judgment::DefEq(lhs, rhs, res) => {
+            let _ = write!(out, "defeq {rule} {} {} {res}", Sexp(lhs), Sexp(rhs));
+        }
+    }
+    for premise in &node.premises {
+        write_node(out, premise, indent + 1);
+    }
+    out.push(')');
+}
+
+fn fingerprint(kernel: &kernel::State) -> String {
+    kernel
+        .fingerprint()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+struct Sexp<'a>(&'a Expr);
+
+impl Display for Sexp<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Expr::FVar(n) => write!(f, "(c {n})"),
+            Expr::BVar(n) => write!(f, "(v {n})"),
+            Expr::Sortω(n) => write!(f, "(ω {n})"),
+            Expr::Nat(n) => write!(f, "(n {n})"),
+            Expr::Lam(l, r) => write!(f, "(λ {} {})", Sexp(l), Sexp(r)),
+            Expr::Pi(l, r) => write!(f, "(Π {} {})", Sexp(l), Sexp(r)),
+            Expr::App(l, r) => write!(f, "(@ {} {})", Sexp(l), Sexp(r)),
+        }
+    }
+}
+
+/// Reads a certificate written by [`write`] for the environment `kernel`.
+pub(crate) fn read(kernel: &kernel::State, text: &str) -> Result<Node, String> {
+    let spaced = text.replace('(', " ( ").replace(')', " ) ");
+    let tokens = &mut spaced.split_whitespace().peekable();
+    let mut expect = |token: &str| match tokens.next() {
+        Some(t) if t == token => Ok(()),
+        t => Err(format!("expected `{token}`, found {t:?}")),
+    };
+    expect("(")?;
+    expect("certificate")?;
+    if tokens.next() != Some(&fingerprint(kernel)) {
+        return Err("the certificate is for another environment".to_owned());
+    }
+    let node = read_node(tokens)?;
+    match (tokens.next(), tokens.next()) {
+        (Some(")"), None) => Ok(node),
+        _ => Err("trailing tokens".to_owned()),
+    }
+}
+
+fn read_node(tokens: &mut Tokens<'_>) -> Result<Node, String> {
+    let (open, kind, rule) = (tokens.next(), tokens.next(), tokens.next());
+    let rule = RULES.iter().find(|(_, name)| Some(*name) == rule);
+    let (Some("("), Some(rule)) = (open, rule) else {
+        return Err("malformed node".to_owned());
+    };
+    let (lhs, rhs) = (read_expr(tokens)?, read_expr(tokens)?);
+    let judgment = match kind {
+        Some("type") => Judgment::Type(lhs, rhs),
+        Some("defeq") => match tokens.next() {
+            Some("true") => Judgment::DefEq(lhs, rhs, true),
+            Some("false") => Judgment::DefEq(lhs, rhs, false),
+            _ => return Err("malformed comparison result".to_owned()),
+        },
+        _ => return Err("malformed judgment".to_owned()),
+    };
+    let mut premises = Vec::new();
+    while tokens.peek() == Some(&"(") {
+        premises.push(read_node(tokens)?);
+    }
+    match tokens.next() {
+        Some(")") => Ok(Node {
+            rule: rule.0,
+            judgment,
+            premises,
+        }),
+        _ => Err("unterminated node".to_owned()),
+    }
+}
+
+fn read_expr(tokens: &mut Tokens<'_>) -> Result<Expr, String> {
+    let (Some("("), Some(head)) = (tokens.next(), tokens.next()) else {
+        return Err("malformed expression".to_owned());
+    };
+    let e = match head {
+        "c" => Expr::FVar(number(tokens)?),
+        "v" => Expr::BVar(number(tokens)?),
+        "ω" => Expr::Sortω(number(tokens)?),
+        "n" => Expr::Nat(
+            tokens
+                .next()
+                .and_then(Nat::parse)
+                .ok_or("malformed literal")?,
+        ),
+        "λ" | "Π" | "@" => {
+            let (l, r) = (read_expr(tokens)?, read_expr(tokens)?);
+            match head {
+                "λ" => r.lam(l),
+                "Π" => r.pi(l),
+                _ => Expr::App(l.into(), r.into()),
+            }
+        }
+        _ => return Err(format!("unknown expression `{head}`")),
+    };
+    match tokens.next() {
+        Some(")") => Ok(e),
+        _ => Err("unterminated expression".to_owned()),
+    }
+}
+
+fn number<T: FromStr>(tokens: &mut Tokens<'_>) -> Result<T, String> {
+    let n = tokens.next().and_then(|n| n.parse().ok());
+    n.ok_or_else(|| "malformed number".to_owned())
+}
+
+type Tokens<'t> = Peekable<SplitWhitespace<'t>>;
+
+use crate::expr::Expr;
+use crate::kernel;
+use crate::kernel::builtins::*;
+use crate::nat::Nat;
+use crate::native::Natives;
+use alloc::borrow::ToOwned;
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use core::fmt::Display;
+use core::fmt::Formatter;
+use core::fmt::Write as _;
+use core::iter::Peekable;
+use core::str::FromStr;
+use core::str::SplitWhitespace;
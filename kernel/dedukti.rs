@@ -0,0 +1,219 @@
+//! Export to Dedukti.
+//!
+//! Types are encoded as codes in a universe `U u`, decoded by `El u`.
+//! Universes are either `sort l` for a level `l` or `sortw n` for a unary natural `n`,
+//! and the `Level` builtins are the prelude's `z`, `s`, `max` and `imax`.
+//! A Π type whose codomain's level depends on the bound variable is encoded with `pid`
+//! and lives in `Sortω₀`, like in the kernel; other Π types use `pi`.
+//! Definitions are exported transparently, and environment names are quoted as `{|name|}`.
+//! `Quot:lift` and the projections of `Sigma` are declared with rewrite rules
+//! for their computation rules, but pairs have no η rule.
+//!
+//! The prelude only normalizes levels by a few rewrite rules,
+//! and proof irrelevance is not encoded,
+//! so proofs that rely on either may be rejected by the third-party checker.
+
+const PRELUDE: &str = "\
+Lvl : Type.
+z : Lvl.
+s : Lvl -> Lvl.
+def max : Lvl -> Lvl -> Lvl.
+[l] max z l --> l.
+[l] max l z --> l.
+[l] max l l --> l.
+[l, m] max (s l) (s m) --> s (max l m).
+def imax : Lvl -> Lvl -> Lvl.
+[l] imax l z --> z.
+[l] imax z l --> l.
+[l] imax l l --> l.
+[l, m] imax l (s m) --> max l (s m).
+
+N : Type.
+nz : N.
+ns : N -> N.
+def nmax : N -> N -> N.
+[n] nmax nz n --> n.
+[n] nmax n nz --> n.
+[n, m] nmax (ns n) (ns m) --> ns (nmax n m).
+
+Univ : Type.
+sort : Lvl -> Univ.
+sortw : N -> Univ.
+def axiom : Univ -> Univ.
+[l] axiom (sort l) --> sort (s l).
+[n] axiom (sortw n) --> sortw (ns n).
+def rule : Univ -> Univ -> Univ.
+[l, m] rule (sort l) (sort m) --> sort (imax l m).
+[n] rule (sort _) (sortw n) --> sortw n.
+[n] rule (sortw n) (sort _) --> sortw n.
+[n, m] rule (sortw n) (sortw m) --> sortw (nmax n m).
+def drule : Univ -> Univ.
+[] drule (sort _) --> sortw nz.
+[n] drule (sortw n) --> sortw n.
+
+U : Univ -> Type.
+def El : u : Univ -> U u -> Type.
+code : u : Univ -> U (axiom u).
+[u] El _ (code u) --> U u.
+Level : U (sort (s z)).
+[] El _ Level --> Lvl.
+def Sort : l : Lvl -> U (sort (s l)) := l => code (sort l).
+pi : a : Univ -> b : Univ -> A : U a -> (El a A -> U b) -> U (rule a b).
+[a, b, A, B] El _ (pi a b A B) --> x : El a A -> El b (B x).
+pid : a : Univ -> A : U a -> m : (El a A -> Lvl) -> (x : El a A -> U (sort (m x))) -> U (drule a).
+[a, A, m, B] El _ (pid a A m B) --> x : El a A -> El (sort (m x)) (B x).
+";
+
+const RESERVED: &[&str] = &[
+    "Lvl", "z", "s", "max", "imax", "N", "nz", "ns", "nmax", "Univ", "sort", "sortw", "axiom",
+    "rule", "drule", "U", "El", "code", "pi", "pid",
+];
+
+pub(crate) fn export(kernel: &kernel::State) -> Result<String, String> {
+    let mut out = PRELUDE.to_owned();
+    for n in BUILTINS as u32..kernel.len() {
+        let (def, name) = (kernel.def(n), kernel.name_of(n));
+        let bound = name
+            .strip_prefix('x')
+            .is_some_and(|n| n.parse::<u32>().is_ok());
+        if RESERVED.contains(&name) || bound || name.contains("|}") {
+            return Err(format!(
+                "`{name}` clashes with a name used by the Dedukti encoding"
+            ));
+        }
+        let mut e = Exporter {
+            kernel,
+            bvars: Vec::new(),
+        };
+        let decl = (|| {
+            let (univ, r#type) = (e.univ(&def.r#type)?, e.expr(&def.r#type)?);
+            Ok::<_, String>(match &def.value {
+                Some(value) => {
+                    format!("def {{|{name}|}} : El {univ} {type} := {}.", e.expr(value)?)
+                }
+                None => match rules(kernel, n) {
+                    Some(rules) => format!("def {{|{name}|}} : El {univ} {type}.\n{rules}"),
+                    None => format!("{{|{name}|}} : El {univ} {type}."),
+                },
+            })
+        })();
+        out.push('\n');
+        out.push_str(&decl.map_err(|err| format!("`{name}`: {err}"))?);
+    }
+    out.push('\n');
+    Ok(out)
+}
+
+/// The rewrite rules that give the axiom `n` its computation rule, if it has one.
+fn rules(kernel: &kernel::State, n: u32) -> Option<String> {
+    let natives = kernel.natives();
+    let quoted = |n| format!("{{|{}|}}", kernel.name_of(n));
+    if let Some([mk, lift]) = natives.quot().filter(|&[_, lift]| lift == n) {
+        let (lift, mk) = (quoted(lift), quoted(mk));
+        return Some(format!(
+            "[u, v, A, r, B, f, h, a] {lift} u v A r B f h ({mk} _ _ _ a) --> f a."
+        ));
+    }
+    let [_, mk, fst, snd] = natives.sigma()?;
+    let field = match n {
+        _ if n == fst => "a",
+        _ if n == snd => "b",
+        _ => return None,
+    };
+    let (projection, mk) = (quoted(n), quoted(mk));
+    Some(format!(
+        "[u, A, B, a, b] {projection} u A B ({mk} _ _ _ a b) --> {field}."
+    ))
+}
+
+struct Exporter<'k> {
+    kernel: &'k kernel::State,
+    /// The types of the enclosing binders, outermost first.
+    bvars: Vec<Expr>,
+}
+
+impl Exporter<'_> {
+    fn expr(&mut self, e: &Expr) -> Result<String, String> {
+        Ok(match e {
+            &Expr::FVar(n) => match n as usize {
+                0 => "Level".to_owned(),
+                1 => "z".to_owned(),
+                2 => "s".to_owned(),
+                3 => "max".to_owned(),
+                4 => "imax".to_owned(),
+                5 => "Sort".to_owned(),
+                _ => format!("{{|{}|}}", self.kernel.name_of(n)),
+            },
+            &Expr::BVar(n) => format!("x{}", self.bvars.len() - 1 - usize::from(n)),
+            &Expr::Sortω(n) => format!("(code {})", sortw(n)),
+            Expr::Nat(_) => return Err("`Nat` literals are not supported".to_owned()),
+            Expr::App(l, r) => format!("({} {})", self.expr(l)?, self.expr(r)?),
+            Expr::Lam(l, r) => self.binder(l, |e| e.expr(r))?,
+            Expr::Pi(l, r) => {
+                let (l_univ, l_expr) = (self.univ(l)?, self.expr(l)?);
+                self.bvars.push((**l).clone());
+                let r_univ = self.kernel.type_in(&self.bvars, r);
+                let r_univ = r_univ.and_then(|univ| match univ {
+                    Expr::App(_, level) if mentions_bvar(&level) => Ok(Err(level)),
+                    univ => self.univ_of_type(univ).map(Ok),
+                });
+                self.bvars.pop();
+                let r_expr = self.binder(l, |e| e.expr(r))?;
+                match r_univ? {
+                    Ok(r_univ) => format!("(pi {l_univ} {r_univ} {l_expr} {r_expr})"),
+                    Err(level) => {
+                        let level = self.binder(l, |e| e.expr(&level))?;
+                        format!("(pid {l_univ} {l_expr} {level} {r_expr})")
+                    }
+                }
+            }
+        })
+    }
+    /// Writes an abstraction over a variable of type `r#type`.
+    fn binder(
+        &mut self,
+        r#type: &Expr,
+        body: impl FnOnce(&mut Self) -> Result<String, String>,
+    ) -> Result<String, String> {
+        let (univ, type_expr) = (self.univ(r#type)?, self.expr(r#type)?);
+        let x = format!("x{}", self.bvars.len());
+        self.bvars.push(r#type.clone());
+        let body = body(self);
+        self.bvars.pop();
+        Ok(format!("({x} : El {univ} {type_expr} => {})", body?))
+    }
+    /// The universe `r#type` lives in, in the current context.
+    fn univ(&mut self, r#type: &Expr) -> Result<String, String> {
+        let univ = self.kernel.type_in(&self.bvars, r#type)?;
+        self.univ_of_type(univ)
+    }
+    fn univ_of_type(&mut self, univ: Expr) -> Result<String, String> {
+        match univ {
+            Expr::App(l, level) if *l == SORT => Ok(format!("(sort {})", self.expr(&level)?)),
+            Expr::Sortω(n) => Ok(format!("({})", sortw(n))),
+            univ => Err(format!("`{}` is not a sort", self.kernel.display(&univ))),
+        }
+    }
+}
+
+fn sortw(n: u16) -> String {
+    let n = (0..n).fold("nz".to_owned(), |n, _| format!("(ns {n})"));
+    format!("sortw {n}")
+}
+
+fn mentions_bvar(e: &Expr) -> bool {
+    e.clone()
+        .try_visit(0, &mut |depth, e| match *e {
+            Expr::BVar(n) if n == depth => Err(()),
+            _ => Ok(()),
+        })
+        .is_err()
+}
+
+use crate::expr::Expr;
+use crate::kernel;
+use crate::kernel::builtins::*;
+use alloc::borrow::ToOwned;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
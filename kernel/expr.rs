@@ -1,8 +1,10 @@
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub(crate) enum Expr {
     FVar(u32),
     BVar(u16),
     Sortω(u16),
+    /// A literal of the native `Nat` type.
+    Nat(Nat),
     Lam(Box<Expr>, Box<Expr>),
     Pi(Box<Expr>, Box<Expr>),
     App(Box<Expr>, Box<Expr>),
@@ -50,6 +52,23 @@ impl Expr {
             _ => panic!(),
         }
     }
+    pub fn for_each_fvar(&self, f: &mut impl FnMut(u32)) {
+        match self {
+            &Self::FVar(n) => f(n),
+            Self::Lam(l, r) | Self::Pi(l, r) | Self::App(l, r) => {
+                (l.for_each_fvar(f), r.for_each_fvar(f)).1
+            }
+            _ => {}
+        }
+    }
+    pub fn try_map_fvars<E>(&mut self, mut f: impl FnMut(u32) -> Result<u32, E>) -> Result<(), E> {
+        self.try_visit(0, &mut |_, e| {
+            if let Self::FVar(n) = e {
+                *n = f(*n)?;
+            }
+            Ok(())
+        })
+    }
     pub fn visit(&mut self, depth: u16, mut f: impl FnMut(u16, &mut Expr)) {
         let _ = self.try_visit(depth, &mut |depth, e| {
             f(depth, e);
@@ -58,4 +77,6 @@ impl Expr {
     }
 }
 
-use std::convert::Infallible;
+use crate::nat::Nat;
+use alloc::boxed::Box;
+use core::convert::Infallible;
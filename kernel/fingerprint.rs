@@ -0,0 +1,76 @@
+//! Content hashes of declarations and environments.
+//!
+//! A declaration's hash covers its name, type and value,
+//! with every constant it mentions replaced by that constant's own hash.
+//! Hashes therefore do not depend on the order in which independent declarations were added.
+
+pub(crate) type Hash = [u8; 32];
+
+pub(crate) fn builtin(name: &str) -> Hash {
+    let mut hasher = Sha256::new();
+    hasher.update(b"builtin");
+    write_str(&mut hasher, name);
+    hasher.finish()
+}
+
+pub(crate) fn declaration(
+    name: &str,
+    r#type: &Expr,
+    value: Option<&Expr>,
+    hash_of: impl Fn(u32) -> Hash,
+) -> Hash {
+    let mut hasher = Sha256::new();
+    hasher.update(match value {
+        None => b"axiom",
+        Some(_) => b"def\0\0",
+    });
+    write_str(&mut hasher, name);
+    write_expr(&mut hasher, r#type, &hash_of);
+    value
+        .iter()
+        .for_each(|v| write_expr(&mut hasher, v, &hash_of));
+    hasher.finish()
+}
+
+/// The hash of a set of declarations, independent of their order.
+pub(crate) fn environment(hashes: impl IntoIterator<Item = Hash>) -> Hash {
+    let mut hashes: Vec<_> = hashes.into_iter().collect();
+    hashes.sort_unstable();
+    let mut hasher = Sha256::new();
+    hasher.update(b"environment");
+    hasher.update(&(hashes.len() as u64).to_le_bytes());
+    hashes.iter().for_each(|h| hasher.update(h));
+    hasher.finish()
+}
+
+fn write_str(hasher: &mut Sha256, s: &str) {
+    hasher.update(&(s.len() as u64).to_le_bytes());
+    hasher.update(s.as_bytes());
+}
+
+fn write_expr(hasher: &mut Sha256, e: &Expr, hash_of: &impl Fn(u32) -> Hash) {
+    match e {
+        &Expr::FVar(n) => (hasher.update(&[0]), hasher.update(&hash_of(n))).1,
+        Expr::BVar(n) => (hasher.update(&[1]), hasher.update(&n.to_le_bytes())).1,
+        Expr::Sortω(n) => (hasher.update(&[2]), hasher.update(&n.to_le_bytes())).1,
+        Expr::Nat(n) => {
+            hasher.update(&[6]);
+            let bytes = n.to_le_bytes();
+            hasher.update(&(bytes.len() as u64).to_le_bytes());
+            hasher.update(&bytes);
+        }
+        Expr::Lam(l, r) | Expr::Pi(l, r) | Expr::App(l, r) => {
+            hasher.update(match e {
+                Expr::Lam(..) => &[3],
+                Expr::Pi(..) => &[4],
+                _ => &[5],
+            });
+            write_expr(hasher, l, hash_of);
+            write_expr(hasher, r, hash_of);
+        }
+    }
+}
+
+use crate::expr::Expr;
+use crate::sha256::Sha256;
+use alloc::vec::Vec;
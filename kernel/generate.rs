@@ -0,0 +1,280 @@
+//! Random well-typed terms over [`crate::AXIOMS`], for fuzzing.
+//!
+//! Terms are generated as source text, directed by a small grammar of types,
+//! and are sprinkled with rewrites that preserve their meaning:
+//! β-expansions, η-expansions of pairs, level identities and sums for literals.
+
+/// The types that terms are generated at.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Type {
+    Bool,
+    Nat,
+    Arrow(Box<Type>, Box<Type>),
+    /// A non-dependent pair of data.
+    Pair(Box<Type>, Box<Type>),
+    /// `Eq (Level:s Level:0) α a b`, where `a` and `b` are definitionally equal.
+    Eq(Box<Type>, String, String),
+}
+
+impl Type {
+    fn is_prop(&self) -> bool {
+        match self {
+            Type::Eq(..) => true,
+            Type::Arrow(_, b) => b.is_prop(),
+            _ => false,
+        }
+    }
+    fn level(&self) -> &'static str {
+        match self.is_prop() {
+            true => "Level:0",
+            false => "Level:s Level:0",
+        }
+    }
+}
+
+impl Display for Type {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Type::Bool => write!(f, "Bool"),
+            Type::Nat => write!(f, "Nat"),
+            Type::Arrow(a, b) => write!(f, "(∀ _: {a}, {b})"),
+            Type::Pair(a, b) => write!(f, "(Sigma (Level:s Level:0) {a} (λ _: {a}, {b}))"),
+            Type::Eq(t, a, b) => write!(f, "(Eq (Level:s Level:0) {t} ({a}) ({b}))"),
+        }
+    }
+}
+
+/// The variables in scope and their types.
+type Scope = Vec<(String, Type)>;
+
+pub(crate) struct Generator {
+    state: u64,
+    names: u32,
+}
+
+impl Generator {
+    pub(crate) fn new(seed: u64) -> Self {
+        let state = seed.wrapping_mul(0x9e37_79b9_7f4a_7c15) | 1;
+        Self { state, names: 0 }
+    }
+    /// A random closed type, a term of that type, and a rewrite of the term.
+    pub(crate) fn sample(&mut self) -> (Type, String, String) {
+        let mut scope = Scope::new();
+        let r#type = self.r#type(2, &mut scope);
+        let term = self.term(&r#type, 4, &mut scope);
+        let rewritten = self.rewrite(&r#type, &term, &mut scope);
+        (r#type, term, rewritten)
+    }
+    /// A random type, whose equations may mention the variables in scope.
+    pub(crate) fn r#type(&mut self, depth: u32, scope: &mut Scope) -> Type {
+        match self.below(4) {
+            0 if depth > 0 => {
+                let data = self.data(depth - 1);
+                let a = self.term(&data, depth - 1, scope);
+                let b = self.rewrite(&data, &a, scope);
+                Type::Eq(Box::new(data), a, b)
+            }
+            1 if depth > 0 => {
+                let a = self.data(depth - 1);
+                Type::Arrow(Box::new(a), Box::new(self.r#type(depth - 1, scope)))
+            }
+            _ => self.data(depth),
+        }
+    }
+    /// A random type of data, in `Sort (Level:s Level:0)`.
+    fn data(&mut self, depth: u32) -> Type {
+        match self.below(if depth == 0 { 2 } else { 4 }) {
+            0 => Type::Bool,
+            1 => Type::Nat,
+            2 => Type::Arrow(
+                Box::new(self.data(depth - 1)),
+                Box::new(self.data(depth - 1)),
+            ),
+            _ => Type::Pair(
+                Box::new(self.data(depth - 1)),
+                Box::new(self.data(depth - 1)),
+            ),
+        }
+    }
+    pub(crate) fn term(&mut self, r#type: &Type, depth: u32, scope: &mut Scope) -> String {
+        let term = match depth == 0 || self.chance(25) {
+            true => self.leaf(r#type, scope),
+            false => self.node(r#type, depth - 1, scope),
+        };
+        match self.chance(15) {
+            true => self.rewrite(r#type, &term, scope),
+            false => term,
+        }
+    }
+    fn leaf(&mut self, r#type: &Type, scope: &mut Scope) -> String {
+        let vars = scope.iter().filter(|(_, t)| t == r#type);
+        let vars = vars.map(|(x, _)| x.clone()).collect::<Vec<_>>();
+        if !vars.is_empty() && self.chance(50) {
+            return vars[self.below(vars.len() as u32) as usize].clone();
+        }
+        self.intro(r#type, 0, scope)
+    }
+    /// A term built from the introduction rules of `type`.
+    fn intro(&mut self, r#type: &Type, depth: u32, scope: &mut Scope) -> String {
+        match r#type {
+            Type::Bool if depth > 0 && self.chance(50) => {
+                let op = ["Nat:beq", "Nat:ble"][self.below(2) as usize];
+                let a = self.term(&Type::Nat, depth - 1, scope);
+                let b = self.term(&Type::Nat, depth - 1, scope);
+                format!("{op} ({a}) ({b})")
+            }
+            Type::Bool => ["false", "true"][self.below(2) as usize].to_owned(),
+            Type::Nat if depth > 0 && self.chance(50) => {
+                let ops = ["Nat:add", "Nat:sub", "Nat:mul", "Nat:div", "Nat:mod"];
+                let op = ops[self.below(5) as usize];
+                let a = self.term(&Type::Nat, depth - 1, scope);
+                let b = self.term(&Type::Nat, depth - 1, scope);
+                format!("{op} ({a}) ({b})")
+            }
+            Type::Nat if depth > 0 && self.chance(50) => {
+                format!("Nat:succ ({})", self.term(&Type::Nat, depth - 1, scope))
+            }
+            Type::Nat => self.below(20).to_string(),
+            Type::Arrow(a, b) => {
+                let x = self.fresh();
+                scope.push((x.clone(), (**a).clone()));
+                let body = self.term(b, depth.saturating_sub(1), scope);
+                scope.pop();
+                format!("λ {x}: {a}, {body}")
+            }
+            Type::Pair(a, b) => {
+                let level = self.level("Level:s Level:0");
+                let x = self.term(a, depth.saturating_sub(1), scope);
+                let y = self.term(b, depth.saturating_sub(1), scope);
+                format!("Sigma:mk ({level}) {a} (λ _: {a}, {b}) ({x}) ({y})")
+            }
+            Type::Eq(t, a, _) => {
+                let level = self.level("Level:s Level:0");
+                format!("Eq:refl ({level}) {t} ({a})")
+            }
+        }
+    }
+    /// A term of `type` built with an elimination rule or an introduction rule.
+    fn node(&mut self, r#type: &Type, depth: u32, scope: &mut Scope) -> String {
+        let level = self.level(r#type.level());
+        match self.below(6) {
+            0 => {
+                let a = self.data(1);
+                let arrow = Type::Arrow(Box::new(a.clone()), Box::new(r#type.clone()));
+                let f = self.term(&arrow, depth, scope);
+                let x = self.term(&a, depth, scope);
+                format!("({f}) ({x})")
+            }
+            1 if !r#type.is_prop() => {
+                let other = self.data(1);
+                let (fst, (a, b)) = match self.chance(50) {
+                    true => (true, (r#type.clone(), other)),
+                    false => (false, (other, r#type.clone())),
+                };
+                let pair = Type::Pair(Box::new(a.clone()), Box::new(b.clone()));
+                let p = self.term(&pair, depth, scope);
+                let projection = if fst { "Sigma:fst" } else { "Sigma:snd" };
+                format!("{projection} ({level}) {a} (λ _: {a}, {b}) ({p})")
+            }
+            2 => {
+                let t = self.term(r#type, depth, scope);
+                let f = self.term(r#type, depth, scope);
+                let b = self.term(&Type::Bool, depth, scope);
+                format!("Bool:elim ({level}) (λ _: Bool, {type}) ({f}) ({t}) ({b})")
+            }
+            3 => {
+                let z = self.term(r#type, depth, scope);
+                let (n, r) = (self.fresh(), self.fresh());
+                scope.extend([(n.clone(), Type::Nat), (r.clone(), r#type.clone())]);
+                let s = self.term(r#type, depth, scope);
+                scope.truncate(scope.len() - 2);
+                let m = self.term(&Type::Nat, depth, scope);
+                format!(
+                    "Nat:elim ({level}) (λ _: Nat, {type}) ({z}) \
+                        (λ {n}: Nat, λ {r}: {type}, {s}) ({m})"
+                )
+            }
+            4 if !r#type.is_prop() => self.lift(r#type, depth, scope),
+            _ => self.intro(r#type, depth, scope),
+        }
+    }
+    /// `Quot:lift` of a function, which respects equality, applied to a `Quot:mk`.
+    fn lift(&mut self, r#type: &Type, depth: u32, scope: &mut Scope) -> String {
+        let (u, v) = (self.level("Level:s Level:0"), self.level("Level:s Level:0"));
+        let t = self.data(1);
+        let relation = format!("(Eq (Level:s Level:0) {t})");
+        let arrow = Type::Arrow(Box::new(t.clone()), Box::new(r#type.clone()));
+        let f = self.term(&arrow, depth, scope);
+        let (a, b, p, c) = (self.fresh(), self.fresh(), self.fresh(), self.fresh());
+        let respects = format!(
+            "λ {a}: {t}, λ {b}: {t}, λ {p}: {relation} {a} {b}, \
+                Eq:elim (Level:s Level:0) Level:0 {t} \
+                    (λ {c}: {t}, Eq (Level:s Level:0) {type} (({f}) {a}) (({f}) {c})) {a} \
+                    (Eq:refl (Level:s Level:0) {type} (({f}) {a})) {b} {p}",
+        );
+        let x = self.term(&t, depth, scope);
+        let q = format!("Quot:mk ({u}) {t} {relation} ({x})");
+        format!("Quot:lift ({u}) ({v}) {t} {relation} {type} ({f}) ({respects}) ({q})")
+    }
+    /// A different term of `type` that is definitionally equal to `term`.
+    pub(crate) fn rewrite(&mut self, r#type: &Type, term: &str, scope: &mut Scope) -> String {
+        let x = self.fresh();
+        match (self.below(5), r#type) {
+            (0, Type::Pair(a, b)) => {
+                let level = self.level("Level:s Level:0");
+                let params = format!("({level}) {a} (λ _: {a}, {b})");
+                format!(
+                    "Sigma:mk {params} (Sigma:fst {params} ({term})) (Sigma:snd {params} ({term}))"
+                )
+            }
+            (0, Type::Nat) if term.parse::<u32>().is_ok() => {
+                let n = term.parse::<u32>().unwrap();
+                let k = self.below(n + 1);
+                format!("Nat:add {k} {}", n - k)
+            }
+            (1, _) => {
+                let level = self.level(r#type.level());
+                format!("(λ u: Level, λ α: Sort u, λ a: α, a) ({level}) {type} ({term})")
+            }
+            (2, _) => {
+                let other = self.data(0);
+                let value = self.term(&other, 0, scope);
+                format!("(λ {x}: {other}, {term}) ({value})")
+            }
+            _ => format!("(λ {x}: {type}, {x}) ({term})"),
+        }
+    }
+    /// A level equal to `level`.
+    fn level(&mut self, level: &str) -> String {
+        if self.chance(70) {
+            return level.to_owned();
+        }
+        let level = match self.below(5) {
+            0 => format!("Level:max ({level}) ({level})"),
+            1 => format!("Level:max Level:0 ({level})"),
+            2 => format!("Level:imax ({level}) ({level})"),
+            3 => format!("Level:imax (Level:s Level:0) ({level})"),
+            _ => format!("Level:max ({level}) (Level:imax Level:0 ({level}))"),
+        };
+        self.level(&level)
+    }
+    fn fresh(&mut self) -> String {
+        self.names += 1;
+        format!("x{}", self.names)
+    }
+    /// A uniform random number below `n`, from xorshift64*.
+    fn below(&mut self, n: u32) -> u32 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        let random = self.state.wrapping_mul(0x2545_f491_4f6c_dd1d) >> 32;
+        (random % u64::from(n)) as u32
+    }
+    fn chance(&mut self, percent: u32) -> bool {
+        self.below(100) < percent
+    }
+}
+
+use std::fmt;
+use std::fmt::Display;
+use std::fmt::Formatter;
@@ -1,5 +1,126 @@
+#[derive(Clone)]
 pub(crate) struct State {
-    defs: Vec<(Rc<str>, Expr)>,
+    defs: Vector<Def>,
+    /// The names of the declarations, and of their namespaces.
+    names: Names,
+    /// Updated by concurrent checks. Each copy of the environment has its own,
+    /// so that restoring a copy rolls them back along with `times`.
+    counters: Counters,
+    natives: Natives,
+    /// The time taken to check each of the last [`MAX_TIMES`] declarations checked,
+    /// in a ring whose oldest entry is at `oldest_time` once it is full.
+    times: Vector<(Arc<str>, Duration)>,
+    oldest_time: u32,
+}
+
+/// The number of check times that are kept.
+pub(crate) const MAX_TIMES: u32 = 1 << 16;
+
+#[derive(Default)]
+struct Counters {
+    type_of_hits: AtomicU64,
+    type_of_misses: AtomicU64,
+    def_eq_hits: AtomicU64,
+    def_eq_misses: AtomicU64,
+    def_eq_calls: AtomicU64,
+    whnf_calls: AtomicU64,
+    beta_reductions: AtomicU64,
+    uip_attempts: AtomicU64,
+    level_solves: AtomicU64,
+    level_vars: AtomicU64,
+    max_depth: AtomicU64,
+}
+
+/// Without 64-bit atomics the counters are not kept, and every statistic is zero.
+#[cfg(not(target_has_atomic = "64"))]
+#[derive(Default)]
+struct AtomicU64;
+
+#[cfg(not(target_has_atomic = "64"))]
+impl AtomicU64 {
+    fn fetch_add(&self, _: u64, _: Ordering) -> u64 {
+        0
+    }
+    fn fetch_max(&self, _: u64, _: Ordering) -> u64 {
+        0
+    }
+    fn load(&self, _: Ordering) -> u64 {
+        0
+    }
+    fn new(_: u64) -> Self {
+        Self
+    }
+}
+
+impl Clone for Counters {
+    fn clone(&self) -> Self {
+        let copy = |counter: &AtomicU64| AtomicU64::new(counter.load(Ordering::Relaxed));
+        Self {
+            type_of_hits: copy(&self.type_of_hits),
+            type_of_misses: copy(&self.type_of_misses),
+            def_eq_hits: copy(&self.def_eq_hits),
+            def_eq_misses: copy(&self.def_eq_misses),
+            def_eq_calls: copy(&self.def_eq_calls),
+            whnf_calls: copy(&self.whnf_calls),
+            beta_reductions: copy(&self.beta_reductions),
+            uip_attempts: copy(&self.uip_attempts),
+            level_solves: copy(&self.level_solves),
+            level_vars: copy(&self.level_vars),
+            max_depth: copy(&self.max_depth),
+        }
+    }
+}
+
+impl Counters {
+    fn add(&self, tally: &Tally) {
+        let add = |counter: &AtomicU64, n| counter.fetch_add(n, Ordering::Relaxed);
+        add(&self.type_of_hits, tally.type_of_hits);
+        add(&self.type_of_misses, tally.type_of_misses);
+        add(&self.def_eq_hits, tally.def_eq_hits);
+        add(&self.def_eq_misses, tally.def_eq_misses);
+        add(&self.def_eq_calls, tally.def_eq_calls);
+        add(&self.whnf_calls, tally.whnf_calls);
+        add(&self.beta_reductions, tally.beta_reductions);
+        add(&self.uip_attempts, tally.uip_attempts);
+        add(&self.level_solves, tally.level_solves);
+        add(&self.level_vars, tally.level_vars);
+        (self.max_depth).fetch_max(tally.max_depth.into(), Ordering::Relaxed);
+    }
+}
+
+/// The counters of one call into the kernel, added to [`Counters`] when it returns.
+#[derive(Default)]
+struct Tally {
+    /// The number of enclosing `type_of` and `def_eq` calls.
+    depth: u32,
+    max_depth: u32,
+    type_of_hits: u64,
+    type_of_misses: u64,
+    def_eq_hits: u64,
+    def_eq_misses: u64,
+    def_eq_calls: u64,
+    whnf_calls: u64,
+    beta_reductions: u64,
+    uip_attempts: u64,
+    level_solves: u64,
+    level_vars: u64,
+}
+
+#[derive(Clone)]
+pub(crate) struct Def {
+    pub name: Name,
+    pub r#type: Expr,
+    /// `None` for builtins and axioms.
+    pub value: Option<Expr>,
+    /// The non-builtin constants mentioned by the type or value, in ascending order.
+    pub deps: Vec<u32>,
+    /// The content hash of the declaration and, transitively, of everything it mentions.
+    pub hash: Hash,
+    /// Whether applying the constant to at most `.0` arguments gives proofs,
+    /// when that does not depend on the arguments.
+    pub proof: Option<(usize, bool)>,
+    /// Whether the declaration is the `sorry` axiom or depends on it.
+    pub sorry: bool,
 }
 
 pub(crate) mod builtins {
@@ -24,88 +145,560 @@ impl State {
             LEVEL.pi(LEVEL).pi(LEVEL),
             SORT.app([LEVEL_S.app([Expr::BVar(0)])]).pi(LEVEL),
         ];
-        let builtin_names = builtin_names.into_iter().map(<Rc<str>>::from);
-        let defs = builtin_names.zip(builtin_types).collect();
-        State { defs }
+        let mut names = Names::new();
+        let defs = builtin_names.into_iter().zip(builtin_types);
+        let defs = defs.map(|(name, r#type)| Def {
+            hash: fingerprint::builtin(name),
+            name: names.intern(name),
+            r#type,
+            value: None,
+            deps: Vec::new(),
+            proof: None,
+            sorry: false,
+        });
+        let st = State {
+            defs: defs.collect(),
+            names,
+            counters: Counters::default(),
+            natives: Natives::default(),
+            times: Vector::new(),
+            oldest_time: 0,
+        };
+        let defs = st.defs.iter().map(|def| Def {
+            proof: st.proof_of_type(&def.r#type),
+            ..def.clone()
+        });
+        State {
+            defs: defs.collect(),
+            names: st.names,
+            counters: st.counters,
+            natives: st.natives,
+            times: st.times,
+            oldest_time: st.oldest_time,
+        }
+    }
+    /// The name with the given text, interning it if needed.
+    pub fn intern(&mut self, name: &str) -> Name {
+        self.names.intern(name)
+    }
+    pub fn names(&self) -> &Names {
+        &self.names
+    }
+    pub fn add(&mut self, name: Name, r#type: Expr, value: Option<Expr>) -> u32 {
+        let text = &self.names[name];
+        let mut deps = Vec::new();
+        let mut push = |n| {
+            if BUILTINS as u32 <= n {
+                deps.push(n);
+            }
+        };
+        r#type.for_each_fvar(&mut push);
+        value.iter().for_each(|v| v.for_each_fvar(&mut push));
+        // Literals depend on `Nat`, so that removing it removes them.
+        if has_literal(&r#type) || value.as_ref().is_some_and(has_literal) {
+            self.natives.nat_constant().into_iter().for_each(&mut push);
+        }
+        deps.sort_unstable();
+        deps.dedup();
+        let hash_of = |n| self.defs[n].hash;
+        let hash = fingerprint::declaration(text, &r#type, value.as_ref(), hash_of);
+        if value.is_none() {
+            self.natives.add(self.defs.len(), text, &r#type);
+        }
+        // `sorry: ∀ u: Level, ∀ α: Sort u, α`, which proves anything.
+        let is_sorry = value.is_none()
+            && text == "sorry"
+            && r#type == Expr::BVar(0).pi(SORT.app([Expr::BVar(0)])).pi(LEVEL);
+        let def = Def {
+            name,
+            proof: self.proof_of_type(&r#type),
+            sorry: is_sorry || deps.iter().any(|&n| self.defs[n].sorry),
+            r#type,
+            value,
+            deps,
+            hash,
+        };
+        self.defs.push(def);
+        self.defs.len() - 1
+    }
+    /// Whether terms of the given type, applied to at most `.0` arguments, are proofs.
+    fn proof_of_type(&self, r#type: &Expr) -> Option<(usize, bool)> {
+        let (mut bvars, mut r#type) = (Vec::new(), r#type.clone());
+        make_whnf(self, &mut r#type);
+        while let Expr::Pi(l, r) = r#type {
+            bvars.push(*l);
+            r#type = *r;
+            make_whnf(self, &mut r#type);
+        }
+        let proof = match self.type_in(&bvars, &r#type).ok()?.into_univ().ok()? {
+            Univ::Sort(level) => level_is_zero(self, &level)?,
+            Univ::Sortω(_) => false,
+        };
+        Some((bvars.len(), proof))
+    }
+    pub fn stats(&self) -> Stats {
+        let get = |counter: &AtomicU64| counter.load(Ordering::Relaxed);
+        let c = &self.counters;
+        Stats {
+            type_of_hits: get(&c.type_of_hits),
+            type_of_misses: get(&c.type_of_misses),
+            def_eq_hits: get(&c.def_eq_hits),
+            def_eq_misses: get(&c.def_eq_misses),
+            def_eq_calls: get(&c.def_eq_calls),
+            whnf_calls: get(&c.whnf_calls),
+            beta_reductions: get(&c.beta_reductions),
+            uip_attempts: get(&c.uip_attempts),
+            level_solves: get(&c.level_solves),
+            level_vars: get(&c.level_vars),
+            max_depth: get(&c.max_depth),
+        }
+    }
+    /// Runs `check`, recording the time it took as that of checking the declaration `name`.
+    pub fn timed<R>(&mut self, name: &str, check: impl FnOnce(&Self) -> R) -> R {
+        let (res, time) = time(|| check(self));
+        self.record_time(name, time);
+        res
+    }
+    /// Records the time taken to check the declaration `name`,
+    /// forgetting the oldest time if [`MAX_TIMES`] are kept. Without `std` nothing is kept.
+    pub fn record_time(&mut self, name: &str, time: Duration) {
+        if cfg!(not(feature = "std")) {
+            return;
+        }
+        let entry = (name.into(), time);
+        if self.times.len() < MAX_TIMES {
+            self.times.push(entry);
+        } else {
+            self.times.set(self.oldest_time, entry);
+            self.oldest_time = (self.oldest_time + 1) % MAX_TIMES;
+        }
+    }
+    /// The time taken to check each of the last declarations, in the order they were checked.
+    pub fn times(&self) -> Vec<(Arc<str>, Duration)> {
+        let oldest = self.oldest_time as usize;
+        let times = self
+            .times
+            .iter()
+            .skip(oldest)
+            .chain(self.times.iter().take(oldest));
+        times.cloned().collect()
+    }
+    pub fn def(&self, n: u32) -> &Def {
+        &self.defs[n]
+    }
+    pub fn len(&self) -> u32 {
+        self.defs.len()
+    }
+    pub fn fingerprint(&self) -> Hash {
+        fingerprint::environment(self.defs.iter().map(|def| def.hash))
+    }
+    /// Every declaration that transitively depends on `n`, in ascending order.
+    pub fn dependents(&self, n: u32) -> Vec<u32> {
+        let mut dependents = Vec::new();
+        for i in n + 1..self.len() {
+            let deps = &self.defs[i].deps;
+            if deps
+                .iter()
+                .any(|d| *d == n || dependents.binary_search(d).is_ok())
+            {
+                dependents.push(i);
+            }
+        }
+        dependents
+    }
+    /// Removes the given sorted declarations, which must be closed under dependents,
+    /// and renumbers the rest.
+    /// Returns the new index of each old declaration.
+    pub fn remove(&mut self, removed: &[u32]) -> Result<Vec<Option<u32>>, String> {
+        if removed.first().is_some_and(|&n| (n as usize) < BUILTINS) {
+            return Err("cannot remove builtins".to_owned());
+        }
+        let mut next = 0;
+        let map: Vec<_> = (0..self.len())
+            .map(|i| match removed.binary_search(&i) {
+                Ok(_) => None,
+                Err(_) => (Some(next), next += 1).0,
+            })
+            .collect();
+        let kept = self.defs.iter().zip(&map).filter(|(_, new)| new.is_some());
+        self.defs = kept
+            .map(|(def, _)| {
+                let mut def = def.clone();
+                let remap = |n: u32| map[n as usize].ok_or(());
+                def.r#type.try_map_fvars(remap).unwrap();
+                def.value
+                    .iter_mut()
+                    .for_each(|v| v.try_map_fvars(remap).unwrap());
+                def.deps.iter_mut().for_each(|d| *d = remap(*d).unwrap());
+                def
+            })
+            .collect();
+        self.natives.remap(&map);
+        Ok(map)
+    }
+    pub fn type_of(&self, value: &Expr) -> Result<Expr, String> {
+        self.context(|cx| type_of(cx, value))
+    }
+    /// Checks that `value` has type `type`, after checking that `type` is a type.
+    pub fn check(&self, value: &Expr, r#type: &Expr) -> Result<(), String> {
+        self.context(|cx| {
+            type_of(cx, r#type)?.expect_univ(cx)?;
+            check(cx, value, &mut r#type.clone())
+        })
+    }
+    /// Checks that `type` is a type.
+    pub fn check_type(&self, r#type: &Expr) -> Result<(), String> {
+        self.context(|cx| type_of(cx, r#type)?.expect_univ(cx).map(drop))
     }
-    pub fn add(&mut self, name: &str, r#type: Expr) -> (Rc<str>, u32) {
-        let name = <Rc<str>>::from(name);
-        self.defs.push((name.clone(), r#type));
-        (name, (self.defs.len() - 1).try_into().unwrap())
+    /// The type of `value` under binders of the given types, outermost first.
+    pub fn type_in(&self, bvars: &[Expr], value: &Expr) -> Result<Expr, String> {
+        self.context(|cx| within(cx, bvars, |cx| type_of(cx, value)))
     }
-    pub fn truncate(&mut self, len: u32) -> Result<(), String> {
-        if (len as usize) < BUILTINS {
-            return Err("cannot truncate builtins".to_owned());
+    /// Like [`Self::type_of`], but also returns the derivation of every judgment made.
+    pub fn derive(&self, value: &Expr) -> Result<(Expr, Node), String> {
+        let trace = &mut Some(vec![Vec::new()]);
+        let r#type = self.context_traced(trace, |cx| type_of(cx, value))?;
+        let mut nodes = trace.take().unwrap().pop().unwrap();
+        Ok((r#type, nodes.pop().unwrap()))
+    }
+    pub fn def_eq(&self, lhs: &Expr, rhs: &Expr) -> bool {
+        let (mut lhs, mut rhs) = (lhs.clone(), rhs.clone());
+        self.context(|cx| def_eq(cx, &mut lhs, &mut rhs))
+    }
+    pub fn whnf(&self, e: &Expr) -> Expr {
+        let mut e = e.clone();
+        make_whnf(self, &mut e);
+        e
+    }
+    pub fn normalize(&self, e: &Expr) -> Expr {
+        let mut e = e.clone();
+        make_nf(self, &mut e);
+        e
+    }
+    pub fn natives(&self) -> &Natives {
+        &self.natives
+    }
+    /// Registers a native extension for the axiom `axiom`,
+    /// with the constants it mentions resolved to `constants`.
+    pub fn extend(
+        &mut self,
+        axiom: u32,
+        constants: Vec<u32>,
+        extension: Arc<dyn Extension>,
+    ) -> Result<(), String> {
+        let def = &self.defs[axiom];
+        if def.value.is_some() || self.natives.is_native(axiom) {
+            let name = &self.names[def.name];
+            return Err(format!(
+                "`{name}` is not an axiom without a native reduction"
+            ));
         }
-        self.defs.truncate(len as usize);
+        let r#type = def.r#type.clone();
+        self.natives.extend(axiom, r#type, constants, extension);
         Ok(())
     }
-    pub fn type_of(&mut self, value: &Expr) -> Result<Expr, String> {
+    /// The axioms that `e` depends on, transitively, in order,
+    /// along with whether they are reduced by native code.
+    pub fn assumptions(&self, e: &Expr) -> Vec<(u32, bool)> {
+        let mut seen = vec![false; self.len() as usize];
+        let mut stack = Vec::new();
+        e.for_each_fvar(&mut |n| stack.push(n));
+        if has_literal(e) {
+            stack.extend(self.natives.nat_constant());
+        }
+        while let Some(n) = stack.pop() {
+            if (n as usize) < BUILTINS || seen[n as usize] {
+                continue;
+            }
+            seen[n as usize] = true;
+            stack.extend(&self.defs[n].deps);
+        }
+        let axioms = (0..self.len()).filter(|&n| seen[n as usize] && self.defs[n].value.is_none());
+        axioms.map(|n| (n, self.natives.is_native(n))).collect()
+    }
+    /// Whether `e` mentions `sorry` or a declaration that depends on it.
+    pub fn uses_sorry(&self, e: &Expr) -> bool {
+        let mut sorry = false;
+        e.for_each_fvar(&mut |n| sorry |= self.defs[n].sorry);
+        sorry
+    }
+    pub fn name_of(&self, fvar: u32) -> &str {
+        &self.names[self.defs[fvar].name]
+    }
+    pub fn display<'a>(&'a self, e: &'a Expr) -> impl Display + 'a {
+        DisplayExpr(self, e)
+    }
+    fn context<R>(&self, f: impl FnOnce(&mut Context<'_>) -> R) -> R {
+        self.context_traced(&mut None, f)
+    }
+    fn context_traced<R>(&self, trace: &mut Trace, f: impl FnOnce(&mut Context<'_>) -> R) -> R {
         let st = self;
         let mut bvars = Vec::new();
         let bvars = Stack::new(&mut bvars);
-        let depth = &mut 0;
-        type_of(&mut Context { st, bvars, depth }, value)
-    }
-    pub fn name_of(&self, fvar: u32) -> &str {
-        &self.defs[fvar as usize].0
+        let tally = &mut Tally::default();
+        let cache = &mut Cache::default();
+        let res = f(&mut Context {
+            st,
+            bvars,
+            tally,
+            trace,
+            cache,
+        });
+        self.counters.add(tally);
+        res
     }
 }
 
 struct Context<'a> {
-    st: &'a mut State,
+    st: &'a State,
     bvars: Stack<'a, &'a Expr>,
-    depth: &'a mut u32,
+    tally: &'a mut Tally,
+    trace: &'a mut Trace,
+    cache: &'a mut Cache,
+}
+
+/// The results of `type_of` and `def_eq` during one call into the kernel.
+/// Closed terms are cached in scope 0, and others in the scope of the innermost binder.
+/// Only terms of at most [`CACHED_SIZE`] nodes are cached, since every lookup clones
+/// and hashes its key, which would take quadratic time on the subterms of a deep term.
+#[derive(Default)]
+struct Cache {
+    types: HashMap<(u32, Expr), Expr>,
+    def_eqs: HashMap<(u32, Expr, Expr), bool>,
+    /// The scope of each enclosing binder, innermost last.
+    scopes: Vec<u32>,
+    next_scope: u32,
+}
+
+/// The number of nodes in the largest terms that are cached.
+pub(crate) const CACHED_SIZE: usize = 256;
+
+impl Cache {
+    /// Whether `exprs` have at most [`CACHED_SIZE`] nodes in total.
+    /// Takes time bounded by [`CACHED_SIZE`].
+    fn cacheable(exprs: &[&Expr]) -> bool {
+        fn within(e: &Expr, budget: &mut usize) -> bool {
+            *budget = match budget.checked_sub(1) {
+                Some(budget) => budget,
+                None => return false,
+            };
+            match e {
+                Expr::Lam(l, r) | Expr::Pi(l, r) | Expr::App(l, r) => {
+                    within(l, budget) && within(r, budget)
+                }
+                Expr::FVar(_) | Expr::BVar(_) | Expr::Sortω(_) | Expr::Nat(_) => true,
+            }
+        }
+        let mut budget = CACHED_SIZE;
+        exprs.iter().all(|e| within(e, &mut budget))
+    }
+    fn scope(&self, exprs: &[&Expr]) -> u32 {
+        match exprs.iter().any(|e| has_loose_bvars(e, 0)) {
+            true => *self.scopes.last().unwrap_or(&0),
+            false => 0,
+        }
+    }
+    fn push_scope(&mut self) {
+        self.next_scope += 1;
+        self.scopes.push(self.next_scope);
+    }
+}
+
+fn has_literal(e: &Expr) -> bool {
+    match e {
+        Expr::Nat(_) => true,
+        Expr::Lam(l, r) | Expr::Pi(l, r) | Expr::App(l, r) => has_literal(l) || has_literal(r),
+        Expr::FVar(_) | Expr::BVar(_) | Expr::Sortω(_) => false,
+    }
+}
+
+pub(crate) fn has_loose_bvars(e: &Expr, depth: u16) -> bool {
+    match e {
+        &Expr::BVar(n) => depth <= n,
+        Expr::Lam(l, r) | Expr::Pi(l, r) => {
+            has_loose_bvars(l, depth) || has_loose_bvars(r, depth + 1)
+        }
+        Expr::App(l, r) => has_loose_bvars(l, depth) || has_loose_bvars(r, depth),
+        Expr::FVar(_) | Expr::Sortω(_) | Expr::Nat(_) => false,
+    }
+}
+
+/// When recording derivations, the premises of each judgment being made, innermost last.
+type Trace = Option<Vec<Vec<Node>>>;
+
+impl Context<'_> {
+    fn enter(&mut self) {
+        self.tally.depth += 1;
+        self.tally.max_depth = Ord::max(self.tally.max_depth, self.tally.depth);
+        if let Some(trace) = self.trace {
+            trace.push(Vec::new());
+        }
+    }
+    fn leave(&mut self, rule: Rule, judgment: impl FnOnce() -> Judgment) {
+        self.tally.depth -= 1;
+        if let Some(trace) = self.trace {
+            let premises = trace.pop().unwrap();
+            let node = Node {
+                rule,
+                judgment: judgment(),
+                premises,
+            };
+            trace.last_mut().unwrap().push(node);
+        }
+    }
+    fn whnf(&mut self, e: &mut Expr) {
+        self.tally.whnf_calls += 1;
+        self.tally.beta_reductions += make_whnf(self.st, e);
+    }
 }
 
 fn type_of(cx: &mut Context<'_>, expr: &Expr) -> Result<Expr, String> {
-    log::trace!("{:4} type_of({})", cx.display(expr), cx.depth);
-    *cx.depth += 1;
+    // Certificates record every judgment, so nothing is cached while recording.
+    let cached = matches!(expr, Expr::Lam(..) | Expr::Pi(..) | Expr::App(..))
+        && cx.trace.is_none()
+        && Cache::cacheable(&[expr]);
+    let key = cached.then(|| (cx.cache.scope(&[expr]), expr.clone()));
+    if let Some(key) = &key {
+        if let Some(r#type) = cx.cache.types.get(key) {
+            cx.tally.type_of_hits += 1;
+            return Ok(r#type.clone());
+        }
+        cx.tally.type_of_misses += 1;
+    }
+    let r#type = infer_checked(cx, expr)?;
+    if let Some(key) = key {
+        cx.cache.types.insert(key, r#type.clone());
+    }
+    Ok(r#type)
+}
+
+fn infer_checked(cx: &mut Context<'_>, expr: &Expr) -> Result<Expr, String> {
+    log::trace!("{:4} type_of({})", cx.tally.depth, cx.display(expr));
+    cx.enter();
     let res = match expr {
-        &Expr::FVar(fvar) => cx.st.defs[fvar as usize].1.clone(),
+        &Expr::FVar(fvar) => cx.st.defs[fvar].r#type.clone(),
         &Expr::BVar(n) => {
             let mut ty = cx.bvars[cx.bvars.len() - 1 - usize::from(n)].clone();
             (ty.raise(0, n + 1), ty).1
         }
         Expr::Sortω(l) => Expr::Sortω(l.checked_add(1).ok_or("Sortω overflow")?),
+        Expr::Nat(_) => cx.st.natives.nat()?,
         Expr::Lam(l, r) => bind(cx, l, |cx, _| Ok(type_of(cx, r)?.pi(l.clone())))?,
         Expr::Pi(l, r) => bind(cx, l, |cx, l_univ| {
-            Ok(match (l_univ, type_of(cx, r)?.expect_univ(cx)?) {
-                (Univ::Sort(l), Univ::Sort(mut r)) => match r.lower(0, 1) {
-                    Ok(()) => SORT.app([LEVEL_IMAX.app([l, r])]),
-                    Err(()) => Expr::Sortω(0),
-                },
-                (Univ::Sortω(a), Univ::Sort(_)) => Expr::Sortω(a),
-                (Univ::Sort(_), Univ::Sortω(a)) => Expr::Sortω(a),
-                (Univ::Sortω(a), Univ::Sortω(b)) => Expr::Sortω(Ord::max(a, b)),
-            })
+            Ok(pi_univ(l_univ, type_of(cx, r)?.expect_univ(cx)?))
         })?,
         Expr::App(l, r) => {
             let mut l_type = type_of(cx, l)?;
-            make_whnf(&mut l_type);
+            cx.whnf(&mut l_type);
             let Expr::Pi(mut f_in, mut f_out) = l_type else {
                 let (l, l_type) = (cx.display(l), cx.display(&l_type));
                 return Err(format!("application LHS `{l} : {l_type}` not Π type"));
             };
-            let mut r_type = type_of(cx, r)?;
-            ensure_def_eq(cx, &mut f_in, &mut r_type)?;
+            if cx.trace.is_some() {
+                let mut r_type = type_of(cx, r)?;
+                ensure_def_eq(cx, &mut f_in, &mut r_type)?;
+            } else {
+                check(cx, r, &mut f_in)?;
+            }
             (f_out.subst(r), *f_out).1
         }
     };
-    *cx.depth -= 1;
-    log::trace!("{:4} type_of result: {}", cx.depth, cx.display(&res));
+    let rule = match expr {
+        Expr::FVar(_) => Rule::Const,
+        Expr::BVar(_) => Rule::Var,
+        Expr::Sortω(_) => Rule::Sortω,
+        Expr::Nat(_) => Rule::Lit,
+        Expr::Lam(..) => Rule::Lam,
+        Expr::Pi(..) => Rule::Pi,
+        Expr::App(..) => Rule::App,
+    };
+    cx.leave(rule, || Judgment::Type(expr.clone(), res.clone()));
+    log::trace!("{:4} type_of result: {}", cx.tally.depth, cx.display(&res));
     Ok(res)
 }
 
+/// Checks that `expr` has type `expected`, pushing the expected type into the bodies of λs
+/// so that a mismatch is reported at the innermost term with the wrong type.
+/// Certificates record inferred types, so this is not used while recording.
+fn check(cx: &mut Context<'_>, expr: &Expr, expected: &mut Expr) -> Result<(), String> {
+    if let Expr::Lam(l, r) = expr {
+        cx.whnf(expected);
+        if let Expr::Pi(domain, codomain) = expected {
+            type_of(cx, l)?.expect_univ(cx)?;
+            let mut binder = (**l).clone();
+            if !def_eq(cx, domain, &mut binder) {
+                let (e, domain, binder) =
+                    (cx.display(expr), cx.display(domain), cx.display(&binder));
+                return Err(format!(
+                    "binder type mismatch in `{e}`:\nexpected {domain}\n   found {binder}"
+                ));
+            }
+            return bind(cx, l, |cx, _| check(cx, r, codomain));
+        }
+    }
+    let mut r#type = type_of(cx, expr)?;
+    if !def_eq(cx, expected, &mut r#type) {
+        let (e, expected, r#type) = (cx.display(expr), cx.display(expected), cx.display(&r#type));
+        return Err(format!(
+            "type mismatch in `{e}`:\nexpected {expected}\n   found {type}",
+            type = r#type
+        ));
+    }
+    Ok(())
+}
+
+/// The sort of a Π type whose domain and codomain live in the given sorts.
+fn pi_univ(l: Univ, r: Univ) -> Expr {
+    match (l, r) {
+        (Univ::Sort(l), Univ::Sort(mut r)) => match r.lower(0, 1) {
+            Ok(()) => SORT.app([LEVEL_IMAX.app([l, r])]),
+            Err(()) => Expr::Sortω(0),
+        },
+        (Univ::Sortω(a), Univ::Sort(_)) => Expr::Sortω(a),
+        (Univ::Sort(_), Univ::Sortω(a)) => Expr::Sortω(a),
+        (Univ::Sortω(a), Univ::Sortω(b)) => Expr::Sortω(Ord::max(a, b)),
+    }
+}
+
+/// Runs `f` under binders of the given types, outermost first.
+fn within<R>(cx: &mut Context<'_>, bvars: &[Expr], f: impl FnOnce(&mut Context<'_>) -> R) -> R {
+    let Some((first, rest)) = bvars.split_first() else {
+        return f(cx);
+    };
+    let (st, tally, trace, cache) = (cx.st, &mut *cx.tally, &mut *cx.trace, &mut *cx.cache);
+    cx.bvars.reborrow().with(first, move |bvars| {
+        cache.push_scope();
+        let mut cx = Context {
+            st,
+            bvars,
+            tally,
+            trace,
+            cache,
+        };
+        let res = within(&mut cx, rest, f);
+        cx.cache.scopes.pop();
+        res
+    })
+}
+
 fn bind<R, F>(cx: &mut Context<'_>, expr: &Expr, f: F) -> Result<R, String>
 where
     F: FnOnce(&mut Context<'_>, Univ) -> Result<R, String>,
 {
     let univ = type_of(cx, expr)?.expect_univ(cx)?;
-    let (st, depth) = (&mut *cx.st, &mut *cx.depth);
+    let (st, tally, trace, cache) = (cx.st, &mut *cx.tally, &mut *cx.trace, &mut *cx.cache);
     cx.bvars.reborrow().with(expr, move |bvars| {
-        let mut cx = Context { st, bvars, depth };
-        f(&mut cx, univ)
+        cache.push_scope();
+        let mut cx = Context {
+            st,
+            bvars,
+            tally,
+            trace,
+            cache,
+        };
+        let res = f(&mut cx, univ);
+        cx.cache.scopes.pop();
+        res
     })
 }
 
@@ -118,17 +711,52 @@ fn ensure_def_eq(cx: &mut Context<'_>, lhs: &mut Expr, rhs: &mut Expr) -> Result
 }
 
 fn def_eq(cx: &mut Context<'_>, lhs: &mut Expr, rhs: &mut Expr) -> bool {
+    cx.tally.def_eq_calls += 1;
+    let cached = cx.trace.is_none() && Cache::cacheable(&[lhs, rhs]);
+    let key = cached.then(|| {
+        let scope = cx.cache.scope(&[lhs, rhs]);
+        (scope, lhs.clone(), rhs.clone())
+    });
+    if let Some(key) = &key {
+        if let Some(&res) = cx.cache.def_eqs.get(key) {
+            cx.tally.def_eq_hits += 1;
+            return res;
+        }
+        cx.tally.def_eq_misses += 1;
+    }
+    let res = compare(cx, lhs, rhs);
+    if let Some(key) = key {
+        cx.cache.def_eqs.insert(key, res);
+    }
+    res
+}
+
+fn compare(cx: &mut Context<'_>, lhs: &mut Expr, rhs: &mut Expr) -> bool {
     let (l, r) = (cx.display(lhs), cx.display(rhs));
-    log::trace!("{:4} def_eq({l}, {r})", cx.depth);
-    *cx.depth += 1;
+    log::trace!("{:4} def_eq({l}, {r})", cx.tally.depth);
+    let original = cx.trace.is_some().then(|| (lhs.clone(), rhs.clone()));
+    cx.enter();
 
-    make_whnf(lhs);
-    make_whnf(rhs);
+    cx.whnf(lhs);
+    cx.whnf(rhs);
 
-    let res = (match (&mut *lhs, &mut *rhs) {
+    let structural = match (&*lhs, &*rhs) {
+        (Expr::Pi(..), _) => Rule::Pi,
+        (Expr::Lam(..), _) => Rule::Lam,
+        (Expr::Nat(_), Expr::App(..)) | (Expr::App(..), Expr::Nat(_)) => Rule::Succ,
+        (Expr::App(..), _) => Rule::App,
+        _ => Rule::Atom,
+    };
+    let structural = (match (&mut *lhs, &mut *rhs) {
         (Expr::FVar(a), Expr::FVar(b)) => a == b,
         (Expr::BVar(n), Expr::BVar(m)) => n == m,
         (Expr::Sortω(n), Expr::Sortω(m)) => n == m,
+        (Expr::Nat(n), Expr::Nat(m)) => n == m,
+        // A literal `n + 1` is `Nat:succ n`.
+        (Expr::Nat(n), e) | (e, Expr::Nat(n)) => match cx.st.natives.succ_of(e) {
+            Some(x) if !n.is_zero() => def_eq(cx, &mut Expr::Nat(n.sub(&Nat::from(1))), x),
+            _ => false,
+        },
         (Expr::Pi(a, b), Expr::Pi(c, d)) => {
             def_eq(cx, a, c) && bind(cx, a, |cx, _| Ok(def_eq(cx, b, d))).unwrap()
         }
@@ -137,19 +765,72 @@ fn def_eq(cx: &mut Context<'_>, lhs: &mut Expr, rhs: &mut Expr) -> bool {
         }
         (Expr::App(a, b), Expr::App(c, d)) => def_eq(cx, a, c) && def_eq(cx, b, d),
         _ => false,
-    }) || level::def_eq(cx, lhs, rhs).unwrap_or(false)
-        || uip(cx, lhs, rhs);
+    })
+    .then_some(structural);
+    let rule = structural
+        .or_else(|| {
+            level::def_eq(cx, lhs, rhs)
+                .unwrap_or(false)
+                .then_some(Rule::Level)
+        })
+        .or_else(|| eta(cx, lhs, rhs).then_some(Rule::Eta))
+        .or_else(|| uip(cx, lhs, rhs).then_some(Rule::Uip));
+    let res = rule.is_some();
 
-    *cx.depth -= 1;
-    log::trace!("{:4} def_eq result: {res}", cx.depth);
+    cx.leave(rule.unwrap_or(Rule::Mismatch), || {
+        let (lhs, rhs) = original.unwrap();
+        Judgment::DefEq(lhs, rhs, res)
+    });
+    log::trace!("{:4} def_eq result: {res}", cx.tally.depth);
     res
 }
 
-// TODO: This is inefficient…
+/// The η rule for pairs: `Sigma:mk u α β a b` is `t` if `t` is in `Sigma u α β`
+/// and its projections are `a` and `b`.
+fn eta(cx: &mut Context<'_>, lhs: &Expr, rhs: &Expr) -> bool {
+    let natives = &cx.st.natives;
+    let Some([sigma, _, fst, snd]) = natives.sigma() else {
+        return false;
+    };
+    let (pair, t) = match (natives.pair_of(lhs), natives.pair_of(rhs)) {
+        (Some(pair), None) => (pair, rhs),
+        (None, Some(pair)) => (pair, lhs),
+        _ => return false,
+    };
+    let [u, alpha, beta, mut a, mut b] = pair.map(Expr::clone);
+    let params = [u, alpha, beta];
+    let project = |n: u32| Expr::FVar(n).app(params.iter().chain([t]).cloned());
+    let mut t_type = match cx.trace {
+        Some(_) => type_of(cx, t).unwrap(),
+        None => infer(cx, t),
+    };
+    def_eq(cx, &mut t_type, &mut Expr::FVar(sigma).app(params.clone()))
+        && def_eq(cx, &mut a, &mut project(fst))
+        && def_eq(cx, &mut b, &mut project(snd))
+}
+
 fn uip(cx: &mut Context<'_>, lhs: &mut Expr, rhs: &mut Expr) -> bool {
-    let not_proof = |e: &Expr| matches!(*e, LEVEL_Z) || e.is_app(&LEVEL_S);
-    let _ = (not_proof(lhs) || not_proof(rhs)) && return false;
+    cx.tally.uip_attempts += 1;
+    let (lhs_proof, rhs_proof) = (proof(cx.st, lhs), proof(cx.st, rhs));
+    let _ = (lhs_proof == Some(false) || rhs_proof == Some(false)) && return false;
+    if cx.trace.is_some() {
+        return uip_checked(cx, lhs, rhs);
+    }
 
+    // Both sides are already known to be well-typed, so their types need not be checked again.
+    let mut lhs_type = infer(cx, lhs);
+    if lhs_proof.is_none() {
+        let Ok(Univ::Sort(mut level)) = infer(cx, &lhs_type).into_univ() else {
+            return false;
+        };
+        let _ = !def_eq(cx, &mut level, &mut LEVEL_Z) && return false;
+    }
+    let mut rhs_type = infer(cx, rhs);
+    def_eq(cx, &mut lhs_type, &mut rhs_type)
+}
+
+/// Like [`uip`], but records the typing judgments that certificates replay.
+fn uip_checked(cx: &mut Context<'_>, lhs: &mut Expr, rhs: &mut Expr) -> bool {
     let mut lhs_sort = type_of(cx, lhs).unwrap();
     if let Univ::Sort(mut level) = type_of(cx, &lhs_sort).unwrap().into_univ().unwrap() {
         if def_eq(cx, &mut level, &mut LEVEL_Z) {
@@ -160,6 +841,68 @@ fn uip(cx: &mut Context<'_>, lhs: &mut Expr, rhs: &mut Expr) -> bool {
     false
 }
 
+/// Whether a well-typed term is a proof, when that is known without inferring its type.
+fn proof(st: &State, e: &Expr) -> Option<bool> {
+    let (mut head, mut args) = (e, 0);
+    while let Expr::App(f, _) = head {
+        (head, args) = (f, args + 1);
+    }
+    match head {
+        Expr::Pi(..) | Expr::Sortω(_) | Expr::Nat(_) => Some(false),
+        Expr::Lam(_, body) if args == 0 => proof(st, body),
+        &Expr::FVar(n) => match st.defs[n].proof {
+            Some((binders, proof)) if args <= binders => Some(proof),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Whether a level is zero, when that does not depend on its variables.
+fn level_is_zero(st: &State, level: &Expr) -> Option<bool> {
+    let mut level = level.clone();
+    make_whnf(st, &mut level);
+    match &level {
+        &LEVEL_Z => Some(true),
+        _ if level.is_app(&LEVEL_S) => Some(false),
+        Expr::App(f, b) if f.is_app(&LEVEL_MAX) => {
+            let Expr::App(_, a) = &**f else {
+                unreachable!()
+            };
+            Some(level_is_zero(st, a)? && level_is_zero(st, b)?)
+        }
+        Expr::App(f, b) if f.is_app(&LEVEL_IMAX) => level_is_zero(st, b),
+        _ => None,
+    }
+}
+
+/// The type of a term that is known to be well-typed.
+fn infer(cx: &mut Context<'_>, expr: &Expr) -> Expr {
+    match expr {
+        &Expr::FVar(fvar) => cx.st.defs[fvar].r#type.clone(),
+        &Expr::BVar(n) => {
+            let mut ty = cx.bvars[cx.bvars.len() - 1 - usize::from(n)].clone();
+            (ty.raise(0, n + 1), ty).1
+        }
+        Expr::Sortω(l) => Expr::Sortω(l + 1),
+        Expr::Nat(_) => cx.st.natives.nat().unwrap(),
+        Expr::Lam(l, r) => within(cx, slice::from_ref(l), |cx| infer(cx, r)).pi(l.clone()),
+        Expr::Pi(l, r) => {
+            let l_univ = infer(cx, l).into_univ();
+            let r_univ = within(cx, slice::from_ref(l), |cx| infer(cx, r)).into_univ();
+            pi_univ(l_univ.unwrap(), r_univ.unwrap())
+        }
+        Expr::App(l, r) => {
+            let mut l_type = infer(cx, l);
+            cx.whnf(&mut l_type);
+            let Expr::Pi(_, mut f_out) = l_type else {
+                unreachable!("ill-typed application");
+            };
+            (f_out.subst(r), *f_out).1
+        }
+    }
+}
+
 mod level {
     pub(super) fn def_eq(cx: &mut Context<'_>, lhs: &mut Expr, rhs: &mut Expr) -> Result<bool, ()> {
         let _ = (!is(lhs) && !is(rhs)) && return Err(());
@@ -172,6 +915,8 @@ mod level {
         max(&mut r, &rhs_term)?;
 
         let vars = vars.exprs.len() as u8;
+        cx.tally.level_solves += 1;
+        cx.tally.level_vars += u64::from(vars);
         log::trace!("{} → {l:?}", cx.display(lhs));
         log::trace!("{} → {r:?}", cx.display(rhs));
         let eq = (0..(1_u16 << vars))
@@ -196,7 +941,7 @@ mod level {
         exprs: Vec<&'e mut Expr>,
     }
     fn term<'e>(vars: &mut Vars<'_, '_, 'e>, e: &'e mut Expr) -> Result<Box<Term>, ()> {
-        make_whnf(e);
+        vars.cx.whnf(e);
         Ok(Box::new(match e {
             &mut LEVEL_Z => Term::Zero,
             _ if e.is_app(&LEVEL_S) => Term::Succ(term(vars, e.unwrap_app().1)?),
@@ -269,16 +1014,50 @@ mod level {
         Some(result)
     }
 
+    use super::log;
     use super::*;
 }
 
-fn make_whnf(e: &mut Expr) {
+/// Runs `f`, returning its result with the time it took.
+/// Without `std` there is no clock, and the time is zero.
+pub(crate) fn time<R>(f: impl FnOnce() -> R) -> (R, Duration) {
+    #[cfg(feature = "std")]
+    {
+        let start = Instant::now();
+        let res = f();
+        (res, start.elapsed())
+    }
+    #[cfg(not(feature = "std"))]
+    (f(), Duration::ZERO)
+}
+
+/// Reduces `e` to weak head normal form, returning the number of β-reductions made.
+fn make_whnf(st: &State, e: &mut Expr) -> u64 {
+    let mut reductions = 0;
     while let Expr::App(l, r) = e {
-        make_whnf(l);
+        reductions += make_whnf(st, l);
         match &mut **l {
             Expr::Lam(_, body) => (body.subst(r), *e = take(body)).1,
             _ => break,
         }
+        reductions += 1;
+    }
+    // The result of an extension need not be in weak head normal form.
+    let whnf = |arg: &mut Expr| reductions += make_whnf(st, arg);
+    if st
+        .natives
+        .reduce(e, whnf, |res, r#type| st.check(res, r#type).is_ok())
+    {
+        reductions += make_whnf(st, e);
+    }
+    reductions
+}
+
+fn make_nf(st: &State, e: &mut Expr) {
+    make_whnf(st, e);
+    match e {
+        Expr::Lam(l, r) | Expr::Pi(l, r) | Expr::App(l, r) => (make_nf(st, l), make_nf(st, r)).1,
+        _ => {}
     }
 }
 
@@ -290,16 +1069,16 @@ impl Expr {
             _ => {}
         });
     }
-    fn subst(&mut self, new: &Expr) {
+    pub(crate) fn subst(&mut self, new: &Expr) {
         self.subst_with(|e| e.clone_from(new));
     }
-    fn raise(&mut self, depth: u16, by: u16) {
+    pub(crate) fn raise(&mut self, depth: u16, by: u16) {
         self.visit(depth, |depth, e| match e {
             Self::BVar(n) if depth <= *n => *n += by,
             _ => {}
         })
     }
-    fn lower(&mut self, depth: u16, by: u16) -> Result<(), ()> {
+    pub(crate) fn lower(&mut self, depth: u16, by: u16) -> Result<(), ()> {
         self.try_visit(depth, &mut |depth, e| {
             match e {
                 &mut Self::BVar(n) if depth <= n && n < depth + by => return Err(()),
@@ -327,18 +1106,19 @@ enum Univ {
     Sortω(u16),
 }
 
-impl<'c> Context<'c> {
-    fn display<'a>(&'a self, e: &'a Expr) -> DisplayExpr<'a, 'c> {
-        DisplayExpr(self, e)
+impl Context<'_> {
+    fn display<'a>(&'a self, e: &'a Expr) -> DisplayExpr<'a> {
+        DisplayExpr(self.st, e)
     }
 }
-struct DisplayExpr<'a, 'c>(&'a Context<'c>, &'a Expr);
-impl Display for DisplayExpr<'_, '_> {
+struct DisplayExpr<'a>(&'a State, &'a Expr);
+impl Display for DisplayExpr<'_> {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match self.1 {
-            &Expr::FVar(n) => f.write_str(self.0.st.name_of(n)),
+            &Expr::FVar(n) => f.write_str(self.0.name_of(n)),
             Expr::BVar(n) => write!(f, "_{n}"),
             Expr::Sortω(n) => write!(f, "Sortω{}", Sub(*n)),
+            Expr::Nat(n) => write!(f, "{n}"),
             Expr::Lam(l, r) | Expr::Pi(l, r) => {
                 let (l_, r) = (Self(self.0, l), Self(self.0, r));
                 let s = match &self.1 {
@@ -375,10 +1155,41 @@ impl Display for Sub {
     }
 }
 
+use crate::certificate::Judgment;
+use crate::certificate::Node;
+use crate::certificate::Rule;
 use crate::expr::Expr;
+use crate::fingerprint;
+use crate::fingerprint::Hash;
+use crate::log;
+use crate::name::Name;
+use crate::name::Names;
+use crate::nat::Nat;
+use crate::native::Extension;
+use crate::native::Natives;
+use crate::persistent::Vector;
 use crate::stack::Stack;
-use std::fmt;
-use std::fmt::Display;
-use std::fmt::Formatter;
-use std::mem::take;
-use std::rc::Rc;
+use crate::Stats;
+use alloc::borrow::ToOwned;
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+use core::fmt::Display;
+use core::fmt::Formatter;
+use core::mem::take;
+use core::slice;
+#[cfg(target_has_atomic = "64")]
+use core::sync::atomic::AtomicU64;
+use core::sync::atomic::Ordering;
+use core::time::Duration;
+// Without `std`, the caches are persistent maps, which are slower but need only `alloc`.
+#[cfg(not(feature = "std"))]
+use crate::persistent::Map as HashMap;
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+#[cfg(feature = "std")]
+use std::time::Instant;
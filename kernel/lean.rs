@@ -0,0 +1,434 @@
+//! An importer for the text format produced by `lean4export`.
+//!
+//! Universe parameters become leading `Level` arguments.
+//! Constants listed in [`MAPPINGS`] are identified with our axioms after checking that their types agree;
+//! other definitions, theorems and axioms are added under their names with `.` replaced by `:`
+//! and checked again, with every definition (but not theorem) they depend on unfolded.
+//! The quotient primitives, which have no types in the export, are identified with ours.
+//! Inductive types, projections and literals are not supported,
+//! and are reported along with every declaration that depends on them.
+
+/// Lean constants and the expressions they are identified with,
+/// which take the Lean universe parameters as their first arguments.
+pub(crate) const MAPPINGS: &[(&str, &str)] = &[
+    ("Eq", "Eq"),
+    ("Eq.refl", "Eq:refl"),
+    (
+        "Eq.ndrec",
+        "λ u: Level, λ v: Level, λ α: Sort v, λ a: α, λ motive: (∀ b: α, Sort u),\
+            Eq:elim v u α motive a",
+    ),
+    ("funext", "funext"),
+    ("Quot.sound", "Quot:sound"),
+    ("Bool", "Bool"),
+    ("Bool.false", "false"),
+    ("Bool.true", "true"),
+    ("Bool.rec", "Bool:elim"),
+    ("Nonempty", "Inhabited"),
+    ("Nonempty.intro", "Inhabited:mk"),
+    (
+        "ULift",
+        "λ r: Level, λ s: Level, ULift (Level:s s) (Level:s r)",
+    ),
+    (
+        "ULift.up",
+        "λ r: Level, λ s: Level, ULift:up (Level:s s) (Level:s r)",
+    ),
+    (
+        "ULift.down",
+        "λ r: Level, λ s: Level, ULift:down (Level:s s) (Level:s r)",
+    ),
+];
+
+/// Imports every declaration in `input`,
+/// returning a report of the mapped and skipped declarations.
+pub(crate) fn import(state: &mut parse::State, input: &str) -> Result<Vec<String>, String> {
+    let mut importer = Importer {
+        state,
+        names: BTreeMap::from([(0, String::new())]),
+        levels: BTreeMap::from([(0, LEVEL_Z)]),
+        nodes: BTreeMap::new(),
+        memo: BTreeMap::new(),
+        consts: BTreeMap::new(),
+        transparent: BTreeSet::new(),
+        output: Vec::new(),
+    };
+    let (mut imported, mut skipped) = (0, 0);
+    for (i, line) in input.lines().enumerate() {
+        let mut tokens = line.split_whitespace();
+        let Some(first) = tokens.next() else {
+            continue;
+        };
+        if i == 0 && first.contains('.') {
+            // The format version.
+            continue;
+        }
+        let res = match first.strip_prefix('#') {
+            Some(_) => importer
+                .declaration(first, &mut tokens)
+                .map(|res| match res {
+                    Ok(n) => imported += n,
+                    Err(()) => skipped += 1,
+                }),
+            None => num(Some(first)).and_then(|i| importer.entry(i, &mut tokens)),
+        };
+        res.and_then(|()| match tokens.next() {
+            Some(t) => Err(format!("unexpected token `{t}`")),
+            None => Ok(()),
+        })
+        .map_err(|e| format!("line {}: {e}", i + 1))?;
+    }
+    let mut output = importer.output;
+    output.push(format!(
+        "imported {imported} declarations, skipped {skipped}"
+    ));
+    Ok(output)
+}
+
+struct Importer<'s> {
+    state: &'s mut parse::State,
+    names: BTreeMap<u32, String>,
+    /// Universe parameters are represented by placeholder constants; see [`param`].
+    levels: BTreeMap<u32, Expr>,
+    nodes: BTreeMap<u32, Node>,
+    /// Translations of the nodes that only mention imported constants.
+    memo: BTreeMap<u32, Expr>,
+    /// The expressions Lean constants translate to, or `Err` if they were skipped.
+    consts: BTreeMap<String, Result<Expr, ()>>,
+    /// The imported definitions that may be unfolded.
+    transparent: BTreeSet<u32>,
+    output: Vec<String>,
+}
+
+enum Node {
+    BVar(u16),
+    Sort(Expr),
+    Const(u32, Vec<Expr>),
+    App(u32, u32),
+    Lam(u32, u32),
+    Pi(u32, u32),
+    Let(u32, u32, u32),
+    Unsupported(&'static str),
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Kind {
+    Axiom,
+    Def,
+    Theorem,
+    Unsupported(&'static str),
+}
+
+impl Importer<'_> {
+    /// Reads a name, universe or expression.
+    fn entry<'i>(&mut self, i: u32, t: &mut impl Iterator<Item = &'i str>) -> Result<(), String> {
+        match t.next().ok_or("missing kind")? {
+            "#NS" | "#NI" => {
+                let prefix = self.name(num(t.next())?)?;
+                let component = t.next().ok_or("missing name component")?;
+                let name = match prefix.is_empty() {
+                    true => component.to_owned(),
+                    false => format!("{prefix}.{component}"),
+                };
+                self.names.insert(i, name);
+            }
+            "#US" => {
+                let level = LEVEL_S.app([self.level(num(t.next())?)?]);
+                self.levels.insert(i, level);
+            }
+            kind @ ("#UM" | "#UIM") => {
+                let f = match kind {
+                    "#UM" => LEVEL_MAX,
+                    _ => LEVEL_IMAX,
+                };
+                let l = self.level(num(t.next())?)?;
+                let r = self.level(num(t.next())?)?;
+                self.levels.insert(i, f.app([l, r]));
+            }
+            "#UP" => {
+                let name = num(t.next())?;
+                self.name(name)?;
+                self.levels.insert(i, param(name));
+            }
+            "#EV" => {
+                self.nodes.insert(i, Node::BVar(num(t.next())?));
+            }
+            "#ES" => {
+                let level = self.level(num(t.next())?)?;
+                self.nodes.insert(i, Node::Sort(level));
+            }
+            "#EC" => {
+                let name = num(t.next())?;
+                self.name(name)?;
+                let levels = t.map(|l| self.level(num(Some(l))?));
+                let levels = levels.collect::<Result<_, _>>()?;
+                self.nodes.insert(i, Node::Const(name, levels));
+            }
+            "#EA" => {
+                let (l, r) = (num(t.next())?, num(t.next())?);
+                self.nodes.insert(i, Node::App(l, r));
+            }
+            kind @ ("#EL" | "#EP") => {
+                // The binder info and name.
+                let mut t = t.skip_while(|t| t.starts_with("#B")).skip(1);
+                let (l, r) = (num(t.next())?, num(t.next())?);
+                let node = match kind {
+                    "#EL" => Node::Lam(l, r),
+                    _ => Node::Pi(l, r),
+                };
+                self.nodes.insert(i, node);
+            }
+            "#EZ" => {
+                let mut t = t.skip(1);
+                let (r#type, value) = (num(t.next())?, num(t.next())?);
+                self.nodes
+                    .insert(i, Node::Let(r#type, value, num(t.next())?));
+            }
+            "#EJ" => {
+                self.nodes.insert(i, Node::Unsupported("projections"));
+                t.for_each(drop);
+            }
+            "#ELN" | "#ELS" => {
+                self.nodes.insert(i, Node::Unsupported("literals"));
+                t.for_each(drop);
+            }
+            // Recursor rules are only used by recursors, which are not supported.
+            "#RR" => t.for_each(drop),
+            kind => return Err(format!("unknown kind `{kind}`")),
+        }
+        Ok(())
+    }
+    /// Reads a declaration, returning how many declarations were imported,
+    /// or `Err(())` if it was skipped.
+    fn declaration<'i>(
+        &mut self,
+        kind: &str,
+        t: &mut impl Iterator<Item = &'i str>,
+    ) -> Result<Result<u32, ()>, String> {
+        if kind == "#QUOT" {
+            for name in ["Quot", "Quot.mk", "Quot.lift", "Quot.ind"] {
+                let mapped = name.replace('.', ":");
+                let res = self.state.check_expr(&mapped).map(|(e, _)| e);
+                match &res {
+                    Ok(_) => self.output.push(format!("mapped `{name}` to `{mapped}`")),
+                    Err(e) => self.output.push(format!("`{name}`: {e}")),
+                }
+                self.consts.insert(name.to_owned(), res.map_err(drop));
+            }
+            return Ok(Ok(0));
+        }
+        let name = num(t.next())?;
+        let r#type = num(t.next())?;
+        let (kind, value) = match kind {
+            "#AX" => (Kind::Axiom, None),
+            "#DEF" => (Kind::Def, Some(num(t.next())?)),
+            "#THM" | "#OPAQUE" => (Kind::Theorem, Some(num(t.next())?)),
+            "#IND" => {
+                skip(t, 4)?;
+                let inductives = num(t.next())?;
+                skip(t, inductives)?;
+                let constructors = num(t.next())?;
+                skip(t, constructors)?;
+                (Kind::Unsupported("inductive types"), None)
+            }
+            "#CTOR" => (skip(t, 4)?, (Kind::Unsupported("constructors"), None)).1,
+            "#REC" => {
+                let inductives = num(t.next())?;
+                skip(t, inductives)?;
+                skip(t, 4)?;
+                let rules: u32 = num(t.next())?;
+                skip(t, rules + 1)?;
+                (Kind::Unsupported("recursors"), None)
+            }
+            kind => return Err(format!("unknown declaration kind `{kind}`")),
+        };
+        let rest: Vec<_> = t.collect();
+        let params = match (kind, &rest[..]) {
+            // Reducibility hints.
+            (Kind::Def, ["R", _, params @ ..] | ["A" | "O", params @ ..]) => params,
+            (_, params) => params,
+        };
+        let params = params.iter().map(|&p| num(Some(p)));
+        let params = params.collect::<Result<Vec<u32>, _>>()?;
+        params.iter().try_for_each(|&p| self.name(p).map(drop))?;
+
+        let name = self.name(name)?.to_owned();
+        if self.consts.contains_key(&name) {
+            return Err(format!("duplicate declaration `{name}`"));
+        }
+        let res = self.add(&name, kind, r#type, value, &params);
+        self.consts.insert(
+            name.clone(),
+            res.as_ref().map(|(e, _)| e.clone()).map_err(drop),
+        );
+        Ok(match res {
+            Ok((_, n)) => Ok(n),
+            Err(e) => (self.output.push(format!("`{name}`: {e}")), Err(())).1,
+        })
+    }
+    /// Adds a declaration, returning the expression it translates to
+    /// and the number of declarations added to the environment.
+    fn add(
+        &mut self,
+        name: &str,
+        kind: Kind,
+        r#type: u32,
+        value: Option<u32>,
+        params: &[u32],
+    ) -> Result<(Expr, u32), String> {
+        let r#type = self.closed(r#type, params, Expr::pi)?;
+        if let Some(&(_, mapped)) = MAPPINGS.iter().find(|&&(lean, _)| lean == name) {
+            let (mapped_expr, mapped_type) = self.state.check_expr(mapped)?;
+            let kernel = self.state.kernel();
+            kernel.type_of(&r#type)?;
+            if !kernel.def_eq(&r#type, &mapped_type) {
+                let (lean, mapped) = (kernel.display(&r#type), kernel.display(&mapped_type));
+                return Err(format!("type `{lean}` does not match `{mapped}`"));
+            }
+            self.output.push(format!("mapped `{name}` to `{mapped}`"));
+            return Ok((mapped_expr, 0));
+        }
+        let value = match (kind, value) {
+            (Kind::Unsupported(what), _) => return Err(format!("unsupported: {what}")),
+            (_, Some(value)) => Some(self.closed(value, params, Expr::lam)?),
+            (_, None) => None,
+        };
+
+        let kernel = self.state.kernel();
+        let mut unfold = BTreeSet::new();
+        let mut stack = Vec::new();
+        r#type.for_each_fvar(&mut |n| stack.push(n));
+        value
+            .iter()
+            .for_each(|v| v.for_each_fvar(&mut |n| stack.push(n)));
+        while let Some(n) = stack.pop() {
+            if self.transparent.contains(&n) && unfold.insert(n) {
+                stack.extend(&kernel.def(n).deps);
+            }
+        }
+
+        let def = Def {
+            name: self.state.intern(&name.replace('.', ":")),
+            r#type,
+            value,
+            deps: Vec::new(),
+            hash: [0; 32],
+            proof: None,
+            sorry: false,
+        };
+        let n = self
+            .state
+            .insert(&def, unfold.into_iter().collect(), true)?;
+        if kind == Kind::Axiom {
+            self.output.push(format!("axiom `{name}`"));
+        }
+        if kind == Kind::Def {
+            self.transparent.insert(n);
+        }
+        Ok((Expr::FVar(n), 1))
+    }
+    /// Translates an expression that may only mention the universe parameters `params`,
+    /// abstracting over them with `bind`.
+    fn closed(
+        &mut self,
+        i: u32,
+        params: &[u32],
+        bind: fn(Expr, Expr) -> Expr,
+    ) -> Result<Expr, String> {
+        let mut e = self.expr(i)?;
+        let mut unbound = None;
+        e.visit(0, |depth, e| match *e {
+            Expr::FVar(n) if n >= PARAMS => match params.iter().position(|&p| param(p) == *e) {
+                Some(i) => *e = Expr::BVar(depth + (params.len() - 1 - i) as u16),
+                None => unbound = Some(u32::MAX - n),
+            },
+            _ => {}
+        });
+        if let Some(p) = unbound {
+            return Err(format!("unbound universe parameter `{}`", self.names[&p]));
+        }
+        Ok(params.iter().fold(e, |e, _| bind(e, LEVEL)))
+    }
+    fn expr(&mut self, i: u32) -> Result<Expr, String> {
+        if let Some(e) = self.memo.get(&i) {
+            return Ok(e.clone());
+        }
+        let node = self
+            .nodes
+            .get(&i)
+            .ok_or_else(|| format!("unknown expression {i}"))?;
+        let e = match *node {
+            Node::BVar(n) => Expr::BVar(n),
+            Node::Sort(ref level) => SORT.app([level.clone()]),
+            Node::Const(name, ref levels) => {
+                let levels = levels.clone();
+                let name = &self.names[&name];
+                let e = match self.consts.get(name) {
+                    Some(Ok(e)) => e.clone(),
+                    Some(Err(())) => return Err(format!("depends on skipped `{name}`")),
+                    None => return Err(format!("unknown constant `{name}`")),
+                };
+                self.state.kernel().whnf(&e.app(levels))
+            }
+            Node::App(l, r) => self.expr(l)?.app([self.expr(r)?]),
+            Node::Lam(l, r) => self.expr(r)?.lam(self.expr(l)?),
+            Node::Pi(l, r) => self.expr(r)?.pi(self.expr(l)?),
+            Node::Let(r#type, value, body) => {
+                let (value, mut body) = (self.expr(value)?, self.expr(body)?);
+                body.subst(&value);
+                body.raise(0, 1);
+                body.lam(self.expr(r#type)?).app([value])
+            }
+            Node::Unsupported(what) => return Err(format!("unsupported: {what}")),
+        };
+        self.memo.insert(i, e.clone());
+        Ok(e)
+    }
+    fn name(&self, i: u32) -> Result<&str, String> {
+        let name = self
+            .names
+            .get(&i)
+            .ok_or_else(|| format!("unknown name {i}"))?;
+        Ok(name)
+    }
+    fn level(&self, i: u32) -> Result<Expr, String> {
+        let level = self
+            .levels
+            .get(&i)
+            .ok_or_else(|| format!("unknown universe {i}"));
+        Ok(level?.clone())
+    }
+}
+
+/// Placeholder constants for universe parameters count down from `u32::MAX`,
+/// and are replaced by bound variables once the declaration's parameters are known.
+const PARAMS: u32 = u32::MAX / 2;
+
+fn param(name: u32) -> Expr {
+    Expr::FVar(u32::MAX - name)
+}
+
+fn num<T: FromStr>(t: Option<&str>) -> Result<T, String> {
+    let t = t.ok_or("unexpected end of line")?;
+    t.parse().map_err(|_| format!("invalid number `{t}`"))
+}
+
+fn skip<'i>(t: &mut impl Iterator<Item = &'i str>, n: u32) -> Result<(), String> {
+    (0..n).try_for_each(|_| {
+        t.next()
+            .map(drop)
+            .ok_or_else(|| "unexpected end of line".to_owned())
+    })
+}
+
+use crate::expr::Expr;
+use crate::kernel::builtins::*;
+use crate::kernel::Def;
+use crate::parse;
+use alloc::borrow::ToOwned;
+use alloc::collections::BTreeMap;
+use alloc::collections::BTreeSet;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::str::FromStr;
@@ -1,3 +1,4 @@
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 #![allow(
     clippy::short_circuit_statement,
     clippy::diverging_sub_expression,
@@ -16,12 +17,231 @@ impl Kernel {
         }
         Self(parse)
     }
-    pub fn add(&mut self, s: &str) -> Result<(), String> {
+    /// Adds the declarations and runs the queries in `s`.
+    ///
+    /// Comments run from `//` to the end of the line, or between a nestable `/*` and `*/`.
+    /// A comment also ends the token before it, so `a//c` is `a` followed by a comment.
+    /// `forall`, `fun` and the tokens that start with `Sortw` are reserved as spellings of
+    /// `∀`, `λ` and `Sortω`, so declarations and variables with these names cannot be used.
+    /// `sorry` is reserved too: it names [`SORRY`], which the environment keeps
+    /// once an accepted declaration uses it, and no declaration can be named `sorry`.
+    pub fn add(&mut self, s: &str) -> Result<Vec<String>, String> {
+        self.0.parse(s).map_err(|e| e.message)
+    }
+    /// Like [`Self::add`], but the error also says where the input was rejected.
+    pub fn add_detailed(&mut self, s: &str) -> Result<Vec<String>, Error> {
         self.0.parse(s)
     }
+    /// Like [`Self::add`], but consecutive definitions are checked on `threads` threads.
+    /// Definitions that fail to check are rejected along with their dependents,
+    /// and the others are committed. Without `std` they are checked on the calling thread.
+    pub fn add_parallel(&mut self, s: &str, threads: usize) -> Result<Vec<String>, String> {
+        self.0.parse_parallel(s, threads).map_err(|e| e.message)
+    }
+    /// Checks an expression and returns its type, displayed in the syntax of [`Self::add`].
+    pub fn type_of(&self, expr: &str) -> Result<String, String> {
+        self.type_of_detailed(expr).map_err(|e| e.message)
+    }
+    /// Like [`Self::type_of`], but the error also says where the expression was rejected.
+    pub fn type_of_detailed(&self, expr: &str) -> Result<String, Error> {
+        let (_, r#type) = self.0.check_expr_detailed(expr)?;
+        let r#type = self.0.kernel().display(&r#type).to_string();
+        Ok(r#type)
+    }
+    /// Serializes the environment in a versioned binary format.
+    pub fn export(&self) -> Vec<u8> {
+        serialize::export(&self.0)
+    }
+    /// Exports the environment as a Dedukti module, to be checked independently.
+    pub fn export_dedukti(&self) -> Result<String, String> {
+        dedukti::export(self.0.kernel())
+    }
+    /// Loads an environment produced by [`Self::export`].
+    /// In `paranoid` mode every declaration is checked again,
+    /// and axioms must be among [`AXIOMS`] or be [`SORRY`], with the same type.
+    pub fn import(bytes: &[u8], paranoid: bool) -> Result<Self, String> {
+        serialize::import(bytes, paranoid).map(Self)
+    }
+    /// A content hash of the whole environment,
+    /// equal for any two environments that accepted the same declarations.
+    pub fn fingerprint(&self) -> [u8; 32] {
+        self.0.kernel().fingerprint()
+    }
+    /// The content hash of a declaration, covering everything it transitively mentions.
+    pub fn fingerprint_of(&self, name: &str) -> Option<[u8; 32]> {
+        Some(self.0.kernel().def(self.0.lookup(name)?).hash)
+    }
+    /// The names and content hashes of every non-builtin declaration, in order.
+    pub fn fingerprints(&self) -> impl Iterator<Item = (&str, [u8; 32])> {
+        let kernel = self.0.kernel();
+        let defs = BUILTINS as u32..kernel.len();
+        defs.map(|n| (kernel.name_of(n), kernel.def(n).hash))
+    }
+    /// The names of the declarations directly in `namespace`, such as `Sigma:mk` in `Sigma`,
+    /// in the order they were first used. The empty namespace holds the names without a `:`.
+    pub fn namespace(&self, namespace: &str) -> impl Iterator<Item = &str> {
+        let kernel = self.0.kernel();
+        self.0.namespace(namespace).map(|n| kernel.name_of(n))
+    }
+    /// Imports the declarations in a `lean4export` file, checking each of them again.
+    /// Returns a report of the declarations that were mapped onto our axioms or skipped.
+    /// If the file is malformed, the environment is left unchanged.
+    pub fn import_lean(&mut self, export: &str) -> Result<Vec<String>, String> {
+        let mut state = self.0.clone();
+        let report = lean::import(&mut state, export)?;
+        self.0 = state;
+        Ok(report)
+    }
+    /// The translation of a term into ZFC, in plain text.
+    /// Large terms translate to functions on sets, written `λ x. …`.
+    pub fn zfc(&mut self, expr: &str) -> Result<String, String> {
+        let (e, _) = self.0.check_expr(expr)?;
+        zfc::translate(self.0.kernel(), &e)?.show(zfc::Syntax::Plain)
+    }
+    /// Like [`Self::zfc`], but in the syntax of Metamath's `set.mm`.
+    /// `U` stands for the sequence of universes, and large terms are rejected.
+    pub fn zfc_metamath(&mut self, expr: &str) -> Result<String, String> {
+        let (e, _) = self.0.check_expr(expr)?;
+        zfc::translate(self.0.kernel(), &e)?.show(zfc::Syntax::Metamath)
+    }
+    /// Checks a term and returns a certificate recording its whole typing derivation.
+    pub fn certify(&mut self, expr: &str) -> Result<String, String> {
+        let (e, _) = self.0.check_expr(expr)?;
+        let (_, derivation) = self.0.kernel().derive(&e)?;
+        Ok(certificate::write(self.0.kernel(), &derivation))
+    }
+    /// Replays a certificate produced by [`Self::certify`] in this environment,
+    /// returning the typing judgment it proves.
+    pub fn check_certificate(&self, certificate: &str) -> Result<String, String> {
+        let kernel = self.0.kernel();
+        let derivation = certificate::read(kernel, certificate)?;
+        let (e, r#type) = certificate::check(kernel, &derivation)?;
+        Ok(format!(
+            "{} : {}",
+            kernel.display(&e),
+            kernel.display(&r#type)
+        ))
+    }
+    /// Registers a native reduction for an axiom, which is added if it does not exist.
+    /// The kernel trusts the reduction, and lists the axiom as native among the assumptions
+    /// of every term that depends on it. Extensions are not exported with the environment.
+    pub fn extend(&mut self, extension: Arc<dyn Extension>) -> Result<(), String> {
+        self.0.extend(extension)
+    }
+    /// The names of the axioms a term depends on transitively, in the order they were added,
+    /// with ` (native)` after those that are reduced by native code.
+    pub fn assumptions(&self, expr: &str) -> Result<Vec<String>, String> {
+        let (e, _) = self.0.check_expr(expr)?;
+        let kernel = self.0.kernel();
+        let axioms = kernel.assumptions(&e).into_iter();
+        let axioms = axioms.map(|(n, native)| match native {
+            true => format!("{} (native)", kernel.name_of(n)),
+            false => kernel.name_of(n).to_owned(),
+        });
+        Ok(axioms.collect())
+    }
+    /// The names of the declarations that depend on `sorry`, in order.
+    pub fn sorries(&self) -> impl Iterator<Item = &str> {
+        let kernel = self.0.kernel();
+        let sorry = self.0.lookup("sorry");
+        let defs = (BUILTINS as u32..kernel.len())
+            .filter(move |&n| kernel.def(n).sorry && Some(n) != sorry);
+        defs.map(|n| kernel.name_of(n))
+    }
+    pub fn stats(&self) -> Stats {
+        self.0.kernel().stats()
+    }
+    /// The wall-clock time taken to check each of the most recently checked declarations,
+    /// in the order they were checked. Axioms are not timed.
+    /// Declarations that were rejected, or checked more than once, are included.
+    /// The times belong to the environment, so [`Self::restore`] rolls them back.
+    /// Without `std` there is no clock, and this is empty.
+    pub fn times(&self) -> Vec<(String, Duration)> {
+        let times = self.0.kernel().times().into_iter();
+        times.map(|(name, time)| (name.to_string(), time)).collect()
+    }
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot(self.0.clone())
+    }
+    pub fn restore(&mut self, snapshot: &Snapshot) {
+        self.0.clone_from(&snapshot.0);
+    }
+}
+
+/// Why [`Kernel::add_detailed`] or [`Kernel::type_of_detailed`] rejected its input.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Error {
+    /// The message that [`Kernel::add`] returns.
+    pub message: String,
+    pub kind: ErrorKind,
+    /// The innermost declaration that was being added, if any.
+    pub declaration: Option<String>,
+    /// The byte offset in the input at which the error was detected: past the token
+    /// that does not parse, or past the value of the declaration that does not check.
+    pub offset: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// The input does not parse, or names an unknown or duplicate declaration.
+    Parse,
+    /// A term does not typecheck, or the kernel rejected a declaration.
+    Check,
+}
+
+/// Counters accumulated by a [`Kernel`]'s environment.
+/// A snapshot keeps a copy, so restoring it rolls them back along with [`Kernel::times`].
+/// They are kept only on targets with 64-bit atomics, and are zero elsewhere.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Stats {
+    pub type_of_hits: u64,
+    pub type_of_misses: u64,
+    pub def_eq_hits: u64,
+    pub def_eq_misses: u64,
+    pub def_eq_calls: u64,
+    /// Reductions to weak head normal form, and the β-reductions they made.
+    pub whnf_calls: u64,
+    pub beta_reductions: u64,
+    /// Comparisons that fell back to proof irrelevance.
+    pub uip_attempts: u64,
+    /// Comparisons decided by the level solver, and the variables they abstracted over.
+    pub level_solves: u64,
+    pub level_vars: u64,
+    /// The deepest nesting of `type_of` and `def_eq` calls.
+    pub max_depth: u64,
 }
 
-const AXIOMS: &str = "\
+impl Stats {
+    /// The fraction of `type_of` calls answered by the inference cache.
+    pub fn type_of_hit_rate(&self) -> f64 {
+        hit_rate(self.type_of_hits, self.type_of_misses)
+    }
+    /// The fraction of `def_eq` calls answered by the conversion cache.
+    pub fn def_eq_hit_rate(&self) -> f64 {
+        hit_rate(self.def_eq_hits, self.def_eq_misses)
+    }
+    /// The mean number of variables in a level solver problem.
+    pub fn mean_level_vars(&self) -> f64 {
+        match self.level_solves {
+            0 => 0.0,
+            solves => self.level_vars as f64 / solves as f64,
+        }
+    }
+}
+
+fn hit_rate(hits: u64, misses: u64) -> f64 {
+    match hits + misses {
+        0 => 0.0,
+        total => hits as f64 / total as f64,
+    }
+}
+
+/// A saved state of a [`Kernel`]'s environment. Taking and restoring snapshots are O(1).
+#[derive(Clone)]
+pub struct Snapshot(parse::State);
+
+/// The axioms every [`Kernel`] starts with, one `name: type` per line.
+pub const AXIOMS: &str = "\
 Eq: ∀ u: Level, ∀ α: Sort u, ∀ a: α, ∀ b: α, Sort Level:0
 Eq:refl: ∀ u: Level, ∀ α: Sort u, ∀ a: α, Eq u α a a
 funext: ∀ u: Level, ∀ v: Level, ∀ α: Sort u, ∀ β: (∀ a: α, Sort v),\
@@ -42,6 +262,8 @@ Sigma:mk_elim: ∀ u: Level, ∀ v: Level, ∀ α: Sort u, ∀ β: (∀ a: α, S
     ∀ motive: (∀ t: Sigma u α β, Sort v), ∀ h: (∀ a: α, ∀ b: β a, motive (Sigma:mk u α β a b)),\
     ∀ a: α, ∀ b: β a,\
     Eq v (motive (Sigma:mk u α β a b)) (Sigma:elim u v α β motive h (Sigma:mk u α β a b)) (h a b)
+Sigma:fst: ∀ u: Level, ∀ α: Sort u, ∀ β: (∀ a: α, Sort u), ∀ t: Sigma u α β, α
+Sigma:snd: ∀ u: Level, ∀ α: Sort u, ∀ β: (∀ a: α, Sort u), ∀ t: Sigma u α β, β (Sigma:fst u α β t)
 Bool: Sort (Level:s Level:0)
 false: Bool
 true: Bool
@@ -72,20 +294,123 @@ W:mk_elim: ∀ u: Level, ∀ v: Level, ∀ α: Sort u, ∀ β: (∀ a: α, Sort
     ∀ a: α, ∀ b: (∀ i: β a, W u α β), Eq v (motive (W:mk u α β a b))\
         (W:elim u v α β motive f (W:mk u α β a b))\
         (f a b (λ i: β a, W:elim u v α β motive f (b i)))
+Nat: Sort (Level:s Level:0)
+Nat:zero: Nat
+Nat:succ: ∀ n: Nat, Nat
+Nat:elim: ∀ u: Level, ∀ motive: (∀ n: Nat, Sort u), ∀ z: motive Nat:zero,\
+    ∀ s: (∀ n: Nat, ∀ r: motive n, motive (Nat:succ n)), ∀ n: Nat, motive n
+Nat:zero_elim: ∀ u: Level, ∀ motive: (∀ n: Nat, Sort u), ∀ z: motive Nat:zero,\
+    ∀ s: (∀ n: Nat, ∀ r: motive n, motive (Nat:succ n)),\
+    Eq u (motive Nat:zero) (Nat:elim u motive z s Nat:zero) z
+Nat:succ_elim: ∀ u: Level, ∀ motive: (∀ n: Nat, Sort u), ∀ z: motive Nat:zero,\
+    ∀ s: (∀ n: Nat, ∀ r: motive n, motive (Nat:succ n)), ∀ n: Nat,\
+    Eq u (motive (Nat:succ n)) (Nat:elim u motive z s (Nat:succ n)) (s n (Nat:elim u motive z s n))
+Nat:add: ∀ a: Nat, ∀ b: Nat, Nat
+Nat:sub: ∀ a: Nat, ∀ b: Nat, Nat
+Nat:mul: ∀ a: Nat, ∀ b: Nat, Nat
+Nat:div: ∀ a: Nat, ∀ b: Nat, Nat
+Nat:mod: ∀ a: Nat, ∀ b: Nat, Nat
+Nat:beq: ∀ a: Nat, ∀ b: Nat, Bool
+Nat:ble: ∀ a: Nat, ∀ b: Nat, Bool
+Nat:add_zero: ∀ n: Nat, Eq (Level:s Level:0) Nat (Nat:add n Nat:zero) n
+Nat:add_succ: ∀ n: Nat, ∀ m: Nat,\
+    Eq (Level:s Level:0) Nat (Nat:add n (Nat:succ m)) (Nat:succ (Nat:add n m))
+Nat:sub_zero: ∀ n: Nat, Eq (Level:s Level:0) Nat (Nat:sub n Nat:zero) n
+Nat:zero_sub: ∀ m: Nat, Eq (Level:s Level:0) Nat (Nat:sub Nat:zero m) Nat:zero
+Nat:succ_sub_succ: ∀ n: Nat, ∀ m: Nat,\
+    Eq (Level:s Level:0) Nat (Nat:sub (Nat:succ n) (Nat:succ m)) (Nat:sub n m)
+Nat:mul_zero: ∀ n: Nat, Eq (Level:s Level:0) Nat (Nat:mul n Nat:zero) Nat:zero
+Nat:mul_succ: ∀ n: Nat, ∀ m: Nat,\
+    Eq (Level:s Level:0) Nat (Nat:mul n (Nat:succ m)) (Nat:add (Nat:mul n m) n)
+Nat:div_zero: ∀ n: Nat, Eq (Level:s Level:0) Nat (Nat:div n Nat:zero) Nat:zero
+Nat:div_lt: ∀ n: Nat, ∀ m: Nat, ∀ h: Eq (Level:s Level:0) Bool (Nat:ble (Nat:succ n) m) true,\
+    Eq (Level:s Level:0) Nat (Nat:div n m) Nat:zero
+Nat:div_add: ∀ n: Nat, ∀ m: Nat, Eq (Level:s Level:0) Nat\
+    (Nat:div (Nat:add n (Nat:succ m)) (Nat:succ m)) (Nat:succ (Nat:div n (Nat:succ m)))
+Nat:mod_zero: ∀ n: Nat, Eq (Level:s Level:0) Nat (Nat:mod n Nat:zero) n
+Nat:mod_lt: ∀ n: Nat, ∀ m: Nat, ∀ h: Eq (Level:s Level:0) Bool (Nat:ble (Nat:succ n) m) true,\
+    Eq (Level:s Level:0) Nat (Nat:mod n m) n
+Nat:mod_add: ∀ n: Nat, ∀ m: Nat, Eq (Level:s Level:0) Nat\
+    (Nat:mod (Nat:add n (Nat:succ m)) (Nat:succ m)) (Nat:mod n (Nat:succ m))
+Nat:zero_beq_zero: Eq (Level:s Level:0) Bool (Nat:beq Nat:zero Nat:zero) true
+Nat:zero_beq_succ: ∀ m: Nat, Eq (Level:s Level:0) Bool (Nat:beq Nat:zero (Nat:succ m)) false
+Nat:succ_beq_zero: ∀ n: Nat, Eq (Level:s Level:0) Bool (Nat:beq (Nat:succ n) Nat:zero) false
+Nat:succ_beq_succ: ∀ n: Nat, ∀ m: Nat,\
+    Eq (Level:s Level:0) Bool (Nat:beq (Nat:succ n) (Nat:succ m)) (Nat:beq n m)
+Nat:zero_ble: ∀ m: Nat, Eq (Level:s Level:0) Bool (Nat:ble Nat:zero m) true
+Nat:succ_ble_zero: ∀ n: Nat, Eq (Level:s Level:0) Bool (Nat:ble (Nat:succ n) Nat:zero) false
+Nat:succ_ble_succ: ∀ n: Nat, ∀ m: Nat,\
+    Eq (Level:s Level:0) Bool (Nat:ble (Nat:succ n) (Nat:succ m)) (Nat:ble n m)
+Quot: ∀ u: Level, ∀ α: Sort u, ∀ r: (∀ a: α, ∀ b: α, Sort Level:0), Sort u
+Quot:mk: ∀ u: Level, ∀ α: Sort u, ∀ r: (∀ a: α, ∀ b: α, Sort Level:0), ∀ a: α, Quot u α r
+Quot:lift: ∀ u: Level, ∀ v: Level, ∀ α: Sort u, ∀ r: (∀ a: α, ∀ b: α, Sort Level:0),\
+    ∀ β: Sort v, ∀ f: (∀ a: α, β), ∀ h: (∀ a: α, ∀ b: α, ∀ p: r a b, Eq v β (f a) (f b)),\
+    ∀ q: Quot u α r, β
+Quot:ind: ∀ u: Level, ∀ α: Sort u, ∀ r: (∀ a: α, ∀ b: α, Sort Level:0),\
+    ∀ motive: (∀ q: Quot u α r, Sort Level:0), ∀ h: (∀ a: α, motive (Quot:mk u α r a)),\
+    ∀ q: Quot u α r, motive q
+Quot:sound: ∀ u: Level, ∀ α: Sort u, ∀ r: (∀ a: α, ∀ b: α, Sort Level:0), ∀ a: α, ∀ b: α,\
+    ∀ p: r a b, Eq u (Quot u α r) (Quot:mk u α r a) (Quot:mk u α r b)
 Inhabited: ∀ u: Level, ∀ α: Sort u, Sort Level:0
 Inhabited:mk: ∀ u: Level, ∀ α: Sort u, ∀ a: α, Inhabited u α
 Inhabited:elim_subsingleton: ∀ u: Level, ∀ v: Level, ∀ α: Sort u, ∀ motive: Sort v,\
-    ∀ h: (∀ a b: motive, Eq v motive a b), ∀ f: (∀ a: α, motive),\
+    ∀ h: (∀ a: motive, ∀ b: motive, Eq v motive a b), ∀ f: (∀ a: α, motive),\
     ∀ t: Inhabited u α, motive
 ";
 
+/// The `sorry` axiom, which proves anything. It is not among [`AXIOMS`]:
+/// a [`Kernel`] declares it the first time its input uses `sorry` as a constant.
+pub const SORRY: &str = "sorry: ∀ u: Level, ∀ α: Sort u, α";
+
+extern crate alloc;
+
+mod log;
+
 mod stack;
 
+mod persistent;
+
+mod name;
+
 mod expr;
 
+mod nat;
+
 mod kernel;
 
+mod native;
+
 mod parse;
 
+mod sha256;
+
+mod fingerprint;
+
+mod lean;
+
+mod dedukti;
+
+mod zfc;
+
+mod certificate;
+
+mod serialize;
+
 #[cfg(test)]
 mod tests;
+
+#[cfg(test)]
+mod generate;
+
+pub use crate::nat::Nat;
+pub use crate::native::Extension;
+pub use crate::native::Term;
+
+use crate::kernel::builtins::BUILTINS;
+use alloc::borrow::ToOwned;
+use alloc::format;
+use alloc::string::String;
+use alloc::string::ToString;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::time::Duration;
@@ -0,0 +1,22 @@
+//! Logging through the `log` crate, or, without `std`, nowhere.
+//! The arguments are still type-checked, but never evaluated.
+
+#[cfg(feature = "std")]
+pub(crate) use ::log::info;
+#[cfg(feature = "std")]
+pub(crate) use ::log::trace;
+
+#[cfg(not(feature = "std"))]
+macro_rules! trace {
+    ($($arg:tt)*) => {
+        if false {
+            let _ = format_args!($($arg)*);
+        }
+    };
+}
+
+#[cfg(not(feature = "std"))]
+pub(crate) use trace;
+
+#[cfg(not(feature = "std"))]
+pub(crate) use trace as info;
@@ -0,0 +1,109 @@
+//! Hierarchical names such as `Sigma:mk_elim`, made of segments separated by `:`.
+//! Names are interned in a persistent table, segment by segment, under their namespace,
+//! so that the members of a namespace can be listed and comparing two names is O(1).
+//! They are also kept by their full text, so that finding a name is a single map lookup,
+//! and a name's index can then locate what it names without hashing again.
+
+/// A name interned in a [`Names`] table.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub(crate) struct Name(u32);
+
+impl Name {
+    /// The namespace of the names with a single segment. It has no text.
+    pub(crate) const ROOT: Name = Name(0);
+    /// The position of the name in its table, which is less than the number of names.
+    pub(crate) fn index(self) -> u32 {
+        self.0
+    }
+}
+
+#[derive(Clone)]
+struct Entry {
+    text: Arc<str>,
+    /// The names in this namespace, by their last segment.
+    members: Map<Arc<str>, Name>,
+    /// The same names, in the order they were interned.
+    order: Vector<Name>,
+}
+
+/// A table of interned names. Cloning it is O(1).
+#[derive(Clone)]
+pub(crate) struct Names {
+    entries: Vector<Entry>,
+    /// Every name but [`Name::ROOT`], by its text.
+    by_text: Map<Arc<str>, Name>,
+}
+
+impl Names {
+    pub fn new() -> Self {
+        let root = Entry {
+            text: "".into(),
+            members: Map::new(),
+            order: Vector::new(),
+        };
+        Self {
+            entries: Vector::from_iter([root]),
+            by_text: Map::new(),
+        }
+    }
+    /// The name with the given text, interning it and its namespaces if needed.
+    pub fn intern(&mut self, text: &str) -> Name {
+        let mut name = Name::ROOT;
+        for (end, segment) in segments(text) {
+            name = match self.member(name, segment) {
+                Some(member) => member,
+                None => self.push(name, &text[..end], segment),
+            };
+        }
+        name
+    }
+    /// The name with the given text, if it was interned.
+    pub fn get(&self, text: &str) -> Option<Name> {
+        self.by_text.get(text).copied()
+    }
+    /// The name `segment` in `namespace`, if it was interned.
+    pub fn member(&self, namespace: Name, segment: &str) -> Option<Name> {
+        self.entries[namespace.0].members.get(segment).copied()
+    }
+    /// The names in `namespace`, in the order they were interned.
+    pub fn members(&self, namespace: Name) -> impl Iterator<Item = Name> + '_ {
+        self.entries[namespace.0].order.iter().copied()
+    }
+    fn push(&mut self, namespace: Name, text: &str, segment: &str) -> Name {
+        let name = Name(self.entries.len());
+        let text: Arc<str> = text.into();
+        self.by_text.insert(text.clone(), name);
+        self.entries.push(Entry {
+            text,
+            members: Map::new(),
+            order: Vector::new(),
+        });
+        let mut entry = self.entries[namespace.0].clone();
+        entry.members.insert(segment.into(), name);
+        entry.order.push(name);
+        self.entries.set(namespace.0, entry);
+        name
+    }
+}
+
+impl Index<Name> for Names {
+    type Output = str;
+    /// The text of `name`, with its segments separated by `:`.
+    fn index(&self, name: Name) -> &str {
+        &self.entries[name.0].text
+    }
+}
+
+/// The segments of `text`, each with the length of the prefix of `text` that ends with it.
+fn segments(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    text.split(':').scan(0, |start, segment| {
+        let end = *start + segment.len();
+        *start = end + 1;
+        Some((end, segment))
+    })
+}
+
+use crate::persistent::Map;
+use crate::persistent::Vector;
+use alloc::sync::Arc;
+use core::ops::Index;
@@ -0,0 +1,197 @@
+//! Arbitrary-precision natural numbers, for the literals of the native `Nat` type.
+//!
+//! The arithmetic follows the `Nat` operations it implements:
+//! subtraction truncates at zero, and division and remainder by zero give zero and the dividend.
+
+/// A natural number, as little-endian base 2³² digits without trailing zeros.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Nat(Arc<[u32]>);
+
+impl Nat {
+    fn from_digits(mut digits: Vec<u32>) -> Self {
+        while digits.last() == Some(&0) {
+            digits.pop();
+        }
+        Self(digits.into())
+    }
+    /// Parses a decimal literal.
+    pub fn parse(s: &str) -> Option<Self> {
+        let _ = (s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit())) && return None;
+        // Nine decimal digits at a time, so that the first chunk may be shorter.
+        let (head, tail) = s.split_at((s.len() - 1) % 9 + 1);
+        let chunks = [head.as_bytes()]
+            .into_iter()
+            .chain(tail.as_bytes().chunks(9));
+        let mut digits = Vec::new();
+        for chunk in chunks {
+            let value = str::from_utf8(chunk).unwrap().parse::<u64>().unwrap();
+            let carry = mul_add(&mut digits, 10_u64.pow(chunk.len() as u32), value);
+            if carry != 0 {
+                digits.push(carry as u32);
+            }
+        }
+        Some(Self::from_digits(digits))
+    }
+    pub fn is_zero(&self) -> bool {
+        self.0.is_empty()
+    }
+    pub fn to_u64(&self) -> Option<u64> {
+        match *self.0 {
+            [] => Some(0),
+            [lo] => Some(lo.into()),
+            [lo, hi] => Some(u64::from(hi) << 32 | u64::from(lo)),
+            _ => None,
+        }
+    }
+    pub fn to_le_bytes(&self) -> Vec<u8> {
+        self.0.iter().flat_map(|d| d.to_le_bytes()).collect()
+    }
+    /// The number whose digits have the given little-endian bytes,
+    /// or `None` if there are trailing zero digits.
+    pub fn from_le_bytes(bytes: &[u8]) -> Option<Self> {
+        let _ = !bytes.len().is_multiple_of(4) && return None;
+        let digits = bytes
+            .chunks(4)
+            .map(|d| u32::from_le_bytes(d.try_into().unwrap()));
+        let digits: Arc<[u32]> = digits.collect();
+        (digits.last() != Some(&0)).then_some(Self(digits))
+    }
+    pub fn add(&self, other: &Self) -> Self {
+        let (long, short) = match self.0.len() < other.0.len() {
+            true => (other, self),
+            false => (self, other),
+        };
+        let mut digits = Vec::with_capacity(long.0.len() + 1);
+        let mut carry = 0;
+        for (i, &d) in long.0.iter().enumerate() {
+            let sum = u64::from(d) + u64::from(short.0.get(i).copied().unwrap_or(0)) + carry;
+            digits.push(sum as u32);
+            carry = sum >> 32;
+        }
+        digits.push(carry as u32);
+        Self::from_digits(digits)
+    }
+    /// `self - other`, or zero if `other` is larger.
+    pub fn sub(&self, other: &Self) -> Self {
+        if self <= other {
+            return Self::default();
+        }
+        let mut digits = Vec::with_capacity(self.0.len());
+        let mut borrow = 0;
+        for (i, &d) in self.0.iter().enumerate() {
+            let sub = i64::from(d) - i64::from(other.0.get(i).copied().unwrap_or(0)) - borrow;
+            digits.push(sub.rem_euclid(1 << 32) as u32);
+            borrow = i64::from(sub < 0);
+        }
+        Self::from_digits(digits)
+    }
+    pub fn mul(&self, other: &Self) -> Self {
+        let mut digits = vec![0_u32; self.0.len() + other.0.len()];
+        for (i, &a) in self.0.iter().enumerate() {
+            let mut carry = 0;
+            for (j, &b) in other.0.iter().enumerate() {
+                let prod = u64::from(a) * u64::from(b) + u64::from(digits[i + j]) + carry;
+                digits[i + j] = prod as u32;
+                carry = prod >> 32;
+            }
+            digits[i + other.0.len()] = carry as u32;
+        }
+        Self::from_digits(digits)
+    }
+    /// The quotient and remainder of `self` by `other`, which are zero and `self` if `other` is zero.
+    pub fn div_rem(&self, other: &Self) -> (Self, Self) {
+        if other.is_zero() {
+            return (Self::default(), self.clone());
+        }
+        if let [divisor] = *other.0 {
+            let mut digits = self.0.to_vec();
+            let rem = div_small(&mut digits, divisor);
+            return (Self::from_digits(digits), Self::from_digits(vec![rem]));
+        }
+        // Binary long division, one bit of the dividend at a time.
+        let mut quotient = vec![0_u32; self.0.len()];
+        let mut rem = Self::default();
+        for bit in (0..self.0.len() * 32).rev() {
+            rem = rem.add(&rem);
+            if self.0[bit / 32] >> (bit % 32) & 1 == 1 {
+                rem = rem.add(&Self::from(1));
+            }
+            if *other <= rem {
+                rem = rem.sub(other);
+                quotient[bit / 32] |= 1 << (bit % 32);
+            }
+        }
+        (Self::from_digits(quotient), rem)
+    }
+}
+
+/// Multiplies `digits` by `factor` and adds `carry`, returning the digit that overflows.
+fn mul_add(digits: &mut [u32], factor: u64, mut carry: u64) -> u64 {
+    for d in digits {
+        let prod = u64::from(*d) * factor + carry;
+        *d = prod as u32;
+        carry = prod >> 32;
+    }
+    carry
+}
+
+/// Divides `digits` by `divisor` in place, returning the remainder.
+fn div_small(digits: &mut [u32], divisor: u32) -> u32 {
+    let mut rem = 0_u64;
+    for d in digits.iter_mut().rev() {
+        let cur = rem << 32 | u64::from(*d);
+        *d = (cur / u64::from(divisor)) as u32;
+        rem = cur % u64::from(divisor);
+    }
+    rem as u32
+}
+
+impl Default for Nat {
+    fn default() -> Self {
+        Self(Arc::new([]))
+    }
+}
+
+impl From<u64> for Nat {
+    fn from(n: u64) -> Self {
+        Self::from_digits(vec![n as u32, (n >> 32) as u32])
+    }
+}
+
+impl Ord for Nat {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let len = self.0.len().cmp(&other.0.len());
+        len.then_with(|| self.0.iter().rev().cmp(other.0.iter().rev()))
+    }
+}
+
+impl PartialOrd for Nat {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Display for Nat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut digits = self.0.to_vec();
+        let mut chunks = Vec::new();
+        while !digits.is_empty() {
+            chunks.push(div_small(&mut digits, 1_000_000_000));
+            while digits.last() == Some(&0) {
+                digits.pop();
+            }
+        }
+        let mut chunks = chunks.iter().rev();
+        write!(f, "{}", chunks.next().unwrap_or(&0))?;
+        chunks.try_for_each(|chunk| write!(f, "{chunk:09}"))
+    }
+}
+
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::fmt;
+use core::fmt::Display;
+use core::fmt::Formatter;
+use core::str;
@@ -0,0 +1,459 @@
+//! Axioms reduced by native code: the `Nat` type and user [`Extension`]s.
+//!
+//! `Nat`, its constructors and its operations are axioms, which the kernel recognizes
+//! by their names and types when they are added. Numerals are literals of type `Nat`,
+//! and `Nat:zero` and `Nat:succ` applied to a literal reduce to literals,
+//! so that the unary encoding and the literals are definitionally equal.
+//! The operations reduce when applied to two literals. On other arguments they are
+//! characterized by their recursion equations, such as `Nat:add_succ`, which are axioms
+//! that hold definitionally on literals.
+//!
+//! The kernel trusts native reductions without checking them,
+//! so every such axiom is reported as native among the assumptions of the terms that use it.
+//!
+//! `Quot:lift` is recognized the same way, to give it its computation rule:
+//! lifting `f` to a quotient and applying it to `Quot:mk … a` reduces to `f a`.
+//! So are the projections `Sigma:fst` and `Sigma:snd`, which reduce on `Sigma:mk`
+//! and give pairs their η rule in `def_eq`.
+
+/// A native reduction for an axiom, which the kernel trusts.
+pub trait Extension: Send + Sync {
+    /// The name of the axiom.
+    fn name(&self) -> &str;
+    /// The type of the axiom, in the syntax of [`Kernel::add`](crate::Kernel::add).
+    fn r#type(&self) -> &str;
+    /// The number of arguments the axiom is reduced at.
+    fn arity(&self) -> usize;
+    /// The constants that arguments and results may mention, by their index in this list.
+    fn constants(&self) -> &[&str] {
+        &[]
+    }
+    /// Reduces the axiom applied to `args`, which are in weak head normal form
+    /// along with the arguments of any applications in them,
+    /// or returns `None` if it does not reduce.
+    /// The kernel checks that the result has the type of the application,
+    /// and leaves the application unreduced if it does not.
+    fn reduce(&self, args: &[Term]) -> Option<Term>;
+}
+
+/// An argument or result of an [`Extension`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Term {
+    Nat(Nat),
+    /// The constant with this index in [`Extension::constants`].
+    Const(usize),
+    App(Box<Term>, Box<Term>),
+    /// Any other expression, which results may not contain.
+    Other,
+}
+
+/// The constants the kernel recognizes, in the order of the indices below.
+const NAMES: [&str; 21] = [
+    "Bool",
+    "false",
+    "true",
+    "Nat",
+    "Nat:zero",
+    "Nat:succ",
+    "Nat:add",
+    "Nat:sub",
+    "Nat:mul",
+    "Nat:div",
+    "Nat:mod",
+    "Nat:beq",
+    "Nat:ble",
+    "Eq",
+    "Quot",
+    "Quot:mk",
+    "Quot:lift",
+    "Sigma",
+    "Sigma:mk",
+    "Sigma:fst",
+    "Sigma:snd",
+];
+const BOOL: usize = 0;
+const FALSE: usize = 1;
+const TRUE: usize = 2;
+const NAT: usize = 3;
+const ZERO: usize = 4;
+const SUCC: usize = 5;
+const ADD: usize = 6;
+const SUB: usize = 7;
+const MUL: usize = 8;
+const DIV: usize = 9;
+const MOD: usize = 10;
+const BEQ: usize = 11;
+const BLE: usize = 12;
+const EQ: usize = 13;
+const QUOT: usize = 14;
+const QUOT_MK: usize = 15;
+const QUOT_LIFT: usize = 16;
+const SIGMA: usize = 17;
+const SIGMA_MK: usize = 18;
+const SIGMA_FST: usize = 19;
+const SIGMA_SND: usize = 20;
+
+/// The constants of the environment that are reduced natively.
+#[derive(Clone, Default)]
+pub(crate) struct Natives {
+    builtin: [Option<u32>; NAMES.len()],
+    extensions: Vec<Registered>,
+}
+
+#[derive(Clone)]
+struct Registered {
+    axiom: u32,
+    r#type: Expr,
+    constants: Vec<u32>,
+    extension: Arc<dyn Extension>,
+}
+
+impl Natives {
+    /// Recognizes the axiom `n`, if it has one of the names above and the expected type.
+    pub fn add(&mut self, n: u32, name: &str, r#type: &Expr) {
+        let Some(i) = NAMES.iter().position(|&native| native == name) else {
+            return;
+        };
+        if self.builtin[i].is_none() && self.expected_type(i).as_ref() == Some(r#type) {
+            self.builtin[i] = Some(n);
+        }
+    }
+    /// Registers an extension for the axiom `axiom` of type `type`,
+    /// with the constants it mentions resolved to `constants`.
+    pub fn extend(
+        &mut self,
+        axiom: u32,
+        r#type: Expr,
+        constants: Vec<u32>,
+        extension: Arc<dyn Extension>,
+    ) {
+        let registered = Registered {
+            axiom,
+            r#type,
+            constants,
+            extension,
+        };
+        self.extensions.push(registered);
+    }
+    /// Whether the constant `n` is reduced natively, or is `Nat`, the type of literals.
+    pub fn is_native(&self, n: u32) -> bool {
+        self.builtin[NAT..=BLE].contains(&Some(n)) || self.extensions.iter().any(|x| x.axiom == n)
+    }
+    /// Renumbers the constants after declarations were removed,
+    /// dropping the extensions whose axiom or constants were removed.
+    pub fn remap(&mut self, map: &[Option<u32>]) {
+        for native in &mut self.builtin {
+            *native = native.and_then(|n| map[n as usize]);
+        }
+        self.extensions.retain_mut(|x| {
+            let remap = |n: &mut u32| map[*n as usize].map(|new| *n = new).is_some();
+            let r#type = x.r#type.try_map_fvars(|n| map[n as usize].ok_or(()));
+            remap(&mut x.axiom) && x.constants.iter_mut().all(remap) && r#type.is_ok()
+        });
+    }
+    fn expected_type(&self, i: usize) -> Option<Expr> {
+        let constant = |j: usize| self.builtin[j].map(Expr::FVar);
+        let (bool, nat) = (constant(BOOL), constant(NAT));
+        let (eq, quot) = (constant(EQ), constant(QUOT));
+        let (sigma, fst) = (constant(SIGMA), constant(SIGMA_FST));
+        let sort = |level: Expr| SORT.app([level]);
+        let bvar = Expr::BVar;
+        // `∀ a: α, ∀ b: α, Sort Level:0`, where `α` is the innermost variable.
+        let relation = || sort(LEVEL_Z).pi(bvar(1)).pi(bvar(0));
+        // `∀ u: Level, ∀ α: Sort u, ∀ β: (∀ a: α, Sort u), body`
+        let sigma_params = |body: Expr| {
+            let family = sort(bvar(2)).pi(bvar(0));
+            body.pi(family).pi(sort(bvar(0))).pi(LEVEL)
+        };
+        Some(match i {
+            BOOL | NAT => sort(LEVEL_S.app([LEVEL_Z])),
+            FALSE | TRUE => bool?,
+            ZERO => nat?,
+            SUCC => nat.clone()?.pi(nat?),
+            ADD..=MOD => nat.clone()?.pi(nat.clone()?).pi(nat?),
+            BEQ | BLE => bool?.pi(nat.clone()?).pi(nat?),
+            EQ => sort(LEVEL_Z)
+                .pi(bvar(1))
+                .pi(bvar(0))
+                .pi(sort(bvar(0)))
+                .pi(LEVEL),
+            QUOT => sort(bvar(2)).pi(relation()).pi(sort(bvar(0))).pi(LEVEL),
+            QUOT_MK => quot?
+                .app([bvar(3), bvar(2), bvar(1)])
+                .pi(bvar(1))
+                .pi(relation())
+                .pi(sort(bvar(0)))
+                .pi(LEVEL),
+            SIGMA => sigma_params(sort(bvar(2))),
+            SIGMA_MK => sigma_params(
+                sigma?
+                    .app([bvar(4), bvar(3), bvar(2)])
+                    .pi(bvar(1).app([bvar(0)]))
+                    .pi(bvar(1)),
+            ),
+            SIGMA_FST => sigma_params(bvar(2).pi(sigma?.app([bvar(2), bvar(1), bvar(0)]))),
+            SIGMA_SND => sigma_params(
+                bvar(1)
+                    .app([fst?.app([bvar(3), bvar(2), bvar(1), bvar(0)])])
+                    .pi(sigma?.app([bvar(2), bvar(1), bvar(0)])),
+            ),
+            _ => {
+                // `∀ a: α, ∀ b: α, ∀ p: r a b, Eq v β (f a) (f b)`
+                let f = |a| bvar(3).app([bvar(a)]);
+                let respects = eq?.app([bvar(7), bvar(4), f(2), f(1)]);
+                let respects = respects.pi(bvar(4).app([bvar(1), bvar(0)]));
+                let respects = respects.pi(bvar(4)).pi(bvar(3));
+                bvar(3)
+                    .pi(quot?.app([bvar(6), bvar(4), bvar(3)]))
+                    .pi(respects)
+                    .pi(bvar(1).pi(bvar(2)))
+                    .pi(sort(bvar(2)))
+                    .pi(relation())
+                    .pi(sort(bvar(1)))
+                    .pi(LEVEL)
+                    .pi(LEVEL)
+            }
+        })
+    }
+    /// The recognized constant with the given name, such as `Nat:add`.
+    pub fn constant(&self, name: &str) -> Option<u32> {
+        let i = NAMES.iter().position(|&native| native == name)?;
+        self.builtin[i]
+    }
+    /// The type of literals.
+    pub fn nat(&self) -> Result<Expr, String> {
+        let nat = self.nat_constant().map(Expr::FVar);
+        nat.ok_or_else(|| "natural number literal without `Nat`".to_owned())
+    }
+    pub fn nat_constant(&self) -> Option<u32> {
+        self.builtin[NAT]
+    }
+    /// `Quot:mk` and `Quot:lift`, if both were recognized.
+    pub fn quot(&self) -> Option<[u32; 2]> {
+        Some([self.builtin[QUOT_MK]?, self.builtin[QUOT_LIFT]?])
+    }
+    /// `Sigma`, `Sigma:mk`, `Sigma:fst` and `Sigma:snd`, if all were recognized.
+    pub fn sigma(&self) -> Option<[u32; 4]> {
+        let constant = |i: usize| self.builtin[i];
+        Some([
+            constant(SIGMA)?,
+            constant(SIGMA_MK)?,
+            constant(SIGMA_FST)?,
+            constant(SIGMA_SND)?,
+        ])
+    }
+    /// If `e` is `Sigma:mk u α β a b`, returns its arguments.
+    pub fn pair_of<'e>(&self, e: &'e Expr) -> Option<[&'e Expr; 5]> {
+        self.args(e, SIGMA_MK)?.try_into().ok()
+    }
+    /// If `e` is `Nat:succ x`, returns `x`.
+    pub fn succ_of<'e>(&self, e: &'e mut Expr) -> Option<&'e mut Expr> {
+        match e {
+            Expr::App(f, x) if self.is(f, SUCC) => Some(x),
+            _ => None,
+        }
+    }
+    fn is(&self, e: &Expr, i: usize) -> bool {
+        matches!((e, self.builtin[i]), (&Expr::FVar(n), Some(native)) if n == native)
+    }
+    /// The arguments that `e` applies the constant `i` to, if it is its head.
+    fn args<'e>(&self, mut e: &'e Expr, i: usize) -> Option<Vec<&'e Expr>> {
+        let mut args = Vec::new();
+        while let Expr::App(f, a) = e {
+            args.push(&**a);
+            e = f;
+        }
+        args.reverse();
+        self.is(e, i).then_some(args)
+    }
+    /// The subexpression of `e` that applies the constant `i` to `arity` arguments,
+    /// if `e` applies it to at least that many.
+    fn applied<'e>(&self, e: &'e mut Expr, i: usize, arity: usize) -> Option<&'e mut Expr> {
+        let extra = self.args(e, i)?.len().checked_sub(arity)?;
+        let mut applied = e;
+        for _ in 0..extra {
+            applied = applied.unwrap_app().0;
+        }
+        Some(applied)
+    }
+    /// Reduces `e`, whose head is not an abstraction, if it is `Nat:zero`, or `Nat:succ`
+    /// or an operation applied to literals, or an extension's axiom applied to its arguments,
+    /// or a `Quot:lift` applied to a `Quot:mk`.
+    /// `whnf` reduces the arguments, and `typed` checks that the result of an extension
+    /// has the given type. Returns whether `e` was reduced.
+    pub fn reduce(
+        &self,
+        e: &mut Expr,
+        mut whnf: impl FnMut(&mut Expr),
+        typed: impl FnOnce(&Expr, &Expr) -> bool,
+    ) -> bool {
+        if !self.extensions.is_empty() && self.extend_reduce(e, &mut whnf, typed) {
+            return true;
+        }
+        if self.builtin[QUOT_LIFT].is_some() && self.lift(e, &mut whnf) {
+            return true;
+        }
+        if self.builtin[SIGMA_MK].is_some() && self.project(e, &mut whnf) {
+            return true;
+        }
+        if self.is(e, ZERO) {
+            *e = Expr::Nat(Nat::default());
+            return true;
+        }
+        let Expr::App(f, y) = e else {
+            return false;
+        };
+        if self.is(f, SUCC) {
+            whnf(y);
+            let Expr::Nat(y) = &**y else {
+                return false;
+            };
+            *e = Expr::Nat(y.add(&Nat::from(1)));
+            return true;
+        }
+        let Expr::App(op, x) = &mut **f else {
+            return false;
+        };
+        let Some(op) = (ADD..=BLE).find(|&i| self.is(op, i)) else {
+            return false;
+        };
+        whnf(x);
+        whnf(y);
+        let (Expr::Nat(x), Expr::Nat(y)) = (&**x, &**y) else {
+            return false;
+        };
+        let bool = |b: bool| self.builtin[if b { TRUE } else { FALSE }].map(Expr::FVar);
+        let res = match op {
+            ADD => Some(Expr::Nat(x.add(y))),
+            SUB => Some(Expr::Nat(x.sub(y))),
+            MUL => Some(Expr::Nat(x.mul(y))),
+            DIV => Some(Expr::Nat(x.div_rem(y).0)),
+            MOD => Some(Expr::Nat(x.div_rem(y).1)),
+            BEQ => bool(x == y),
+            _ => bool(x <= y),
+        };
+        res.map(|res| *e = res).is_some()
+    }
+    /// Reduces `Quot:lift u v α r β f h (Quot:mk u α r a)`, possibly applied to more arguments,
+    /// to `f a`.
+    fn lift(&self, e: &mut Expr, whnf: &mut impl FnMut(&mut Expr)) -> bool {
+        let Some(lift) = self.applied(e, QUOT_LIFT, 8) else {
+            return false;
+        };
+        let q = lift.unwrap_app().1;
+        whnf(q);
+        let a = match self.args(q, QUOT_MK).as_deref() {
+            Some(&[_, _, _, a]) => a.clone(),
+            _ => return false,
+        };
+        let f = lift.unwrap_app().0.unwrap_app().0.unwrap_app().1.clone();
+        *lift = f.app([a]);
+        true
+    }
+    /// Reduces `Sigma:fst u α β (Sigma:mk u α β a b)` to `a` and `Sigma:snd` of it to `b`,
+    /// possibly applied to more arguments.
+    fn project(&self, e: &mut Expr, whnf: &mut impl FnMut(&mut Expr)) -> bool {
+        let (projection, field) = if self.args(e, SIGMA_FST).is_some() {
+            (SIGMA_FST, 3)
+        } else if self.args(e, SIGMA_SND).is_some() {
+            (SIGMA_SND, 4)
+        } else {
+            return false;
+        };
+        let Some(projection) = self.applied(e, projection, 4) else {
+            return false;
+        };
+        let t = projection.unwrap_app().1;
+        whnf(t);
+        let Some(value) = self.pair_of(t).map(|pair| pair[field].clone()) else {
+            return false;
+        };
+        *projection = value;
+        true
+    }
+    /// Reduces an extension's axiom applied to its arguments,
+    /// if the result has the type of the application.
+    fn extend_reduce(
+        &self,
+        e: &mut Expr,
+        whnf: &mut impl FnMut(&mut Expr),
+        typed: impl FnOnce(&Expr, &Expr) -> bool,
+    ) -> bool {
+        let (mut head, mut arity) = (&*e, 0);
+        while let Expr::App(f, _) = head {
+            (head, arity) = (f, arity + 1);
+        }
+        let registered = self
+            .extensions
+            .iter()
+            .find(|x| *head == Expr::FVar(x.axiom) && x.extension.arity() == arity);
+        let Some(registered) = registered else {
+            return false;
+        };
+        let (mut args, mut spine) = (Vec::new(), &mut *e);
+        while let Expr::App(f, arg) = spine {
+            args.push(registered.term(arg, whnf));
+            spine = f;
+        }
+        args.reverse();
+        let res = registered.extension.reduce(&args);
+        let Some(res) = res.and_then(|res| registered.expr(&res)) else {
+            return false;
+        };
+        match registered.codomain(e) {
+            Some(r#type) if typed(&res, &r#type) => (*e = res, true).1,
+            _ => false,
+        }
+    }
+}
+
+impl Registered {
+    /// The type of `e`, which applies the axiom to its arguments,
+    /// if it does not mention bound variables.
+    fn codomain(&self, mut e: &Expr) -> Option<Expr> {
+        let mut args = Vec::new();
+        while let Expr::App(f, a) = e {
+            args.push(&**a);
+            e = f;
+        }
+        let mut r#type = self.r#type.clone();
+        for arg in args.into_iter().rev() {
+            let Expr::Pi(_, mut codomain) = r#type else {
+                return None;
+            };
+            codomain.subst(arg);
+            r#type = *codomain;
+        }
+        (!has_loose_bvars(&r#type, 0)).then_some(r#type)
+    }
+    fn term(&self, e: &mut Expr, whnf: &mut impl FnMut(&mut Expr)) -> Term {
+        whnf(e);
+        match e {
+            Expr::Nat(n) => Term::Nat(n.clone()),
+            &mut Expr::FVar(n) => match self.constants.iter().position(|&c| c == n) {
+                Some(i) => Term::Const(i),
+                None => Term::Other,
+            },
+            Expr::App(f, a) => Term::App(self.term(f, whnf).into(), self.term(a, whnf).into()),
+            _ => Term::Other,
+        }
+    }
+    fn expr(&self, term: &Term) -> Option<Expr> {
+        Some(match term {
+            Term::Nat(n) => Expr::Nat(n.clone()),
+            &Term::Const(i) => Expr::FVar(*self.constants.get(i)?),
+            Term::App(f, a) => self.expr(f)?.app([self.expr(a)?]),
+            Term::Other => return None,
+        })
+    }
+}
+
+use crate::expr::Expr;
+use crate::kernel::builtins::*;
+use crate::kernel::has_loose_bvars;
+use crate::nat::Nat;
+use alloc::borrow::ToOwned;
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
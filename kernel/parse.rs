@@ -1,7 +1,12 @@
+#[derive(Clone)]
 pub(crate) struct State {
     kernel: kernel::State,
-    defs: HashMap<Rc<str>, u32>,
-    vals: Vec<Expr>,
+    /// The declaration with each name, by the index of the name.
+    defs: Vector<Option<u32>>,
+    /// The definitions whose `with` block is being checked, along with their unfolded values.
+    unfolding: Vec<(u32, Expr)>,
+    /// For each declaration, the enclosing definitions that were unfolded while checking it.
+    unfolds: Vector<Vec<u32>>,
 }
 
 impl State {
@@ -15,92 +20,531 @@ impl State {
             "Sort",
         ];
         let kernel = kernel::State::new(builtins);
-        let defs = builtins.into_iter().enumerate();
-        let defs = defs.map(|(i, name)| (name.into(), i as u32)).collect();
-        let vals = (0..BUILTINS).map(|i| Expr::FVar(i as u32)).collect();
-        Self { kernel, defs, vals }
+        let defs = defs_by_name(&kernel);
+        let unfolding = Vec::new();
+        let unfolds = (0..BUILTINS).map(|_| Vec::new()).collect();
+        Self {
+            kernel,
+            defs,
+            unfolding,
+            unfolds,
+        }
+    }
+    pub fn parse(&mut self, input: &str) -> Result<Vec<String>, Error> {
+        self.commands(input, None)
+    }
+    pub fn parse_parallel(&mut self, input: &str, threads: usize) -> Result<Vec<String>, Error> {
+        self.commands(input, Some(threads))
     }
-    pub fn parse(&mut self, input: &str) -> Result<(), String> {
-        let mut input = input.trim_start_matches(['\n', '\t', ' ']);
+    fn commands(&mut self, input: &str, threads: Option<usize>) -> Result<Vec<String>, Error> {
+        let mut rest = input;
+        let res = match self.declare_sorry(input) {
+            Ok(true) => {
+                let res = self.run(&mut rest, threads);
+                let forgotten = self.forget_sorry().map_err(checked);
+                res.and_then(|output| forgotten.map(|()| output))
+            }
+            Ok(false) => self.run(&mut rest, threads),
+            Err(e) => Err(Failure::from(e)),
+        };
+        res.map_err(|failure| failure.locate(input, rest))
+    }
+    /// Declares [`SORRY`] if `input` mentions `sorry` and it is not declared,
+    /// returning whether it did, so that an environment can only prove anything
+    /// if its input asks to. [`Self::forget_sorry`] then removes it unless it was used.
+    fn declare_sorry(&mut self, input: &str) -> Result<bool, String> {
+        if !input.contains("sorry") || self.lookup("sorry").is_some() {
+            return Ok(false);
+        }
+        let mut rest = input;
+        skip_trivia(&mut rest);
+        while let Some(t) = token(&mut rest) {
+            if t == "sorry" {
+                return self.axiom(SORRY).map(|()| true);
+            }
+        }
+        Ok(false)
+    }
+    /// Removes [`SORRY`] if no declaration depends on it, as when `sorry` only named
+    /// a bound variable or every declaration that used it was rejected.
+    fn forget_sorry(&mut self) -> Result<(), String> {
+        match self.lookup("sorry") {
+            Some(n) if self.kernel.dependents(n).is_empty() => self.remove(&[n]).map(drop),
+            _ => Ok(()),
+        }
+    }
+    fn run(&mut self, input: &mut &str, threads: Option<usize>) -> Result<Vec<String>, Failure> {
+        let mut output = Vec::new();
+        let mut batch = None;
+        skip_trivia(input);
         while !input.is_empty() {
-            if peek(input) == Some(["remove"]) {
-                exact_token(&mut input, "remove")?;
-                let n = fvar(&self.defs, token(&mut input).ok_or("unexpected EOF")?)?;
-                self.kernel.truncate(n)?;
-                self.vals.truncate(n as usize);
-                for i in n..self.defs.len() as u32 {
-                    self.defs.remove(self.kernel.name_of(i));
+            let command = peek(input);
+            if let (Some(threads), Some(start)) = (threads, batch) {
+                if command != Some(["def"]) {
+                    self.commit(start, threads)?;
+                    batch = None;
+                }
+            }
+            match command {
+                Some(["remove"]) => {
+                    exact_token(input, "remove")?;
+                    let n = fvar(self, token(input).ok_or("unexpected EOF")?)?;
+                    let mut removed = self.kernel.dependents(n);
+                    removed.insert(0, n);
+                    let (removed, _) = self.remove(&removed).map_err(checked)?;
+                    for (_, def, _) in &removed[1..] {
+                        let name = &self.kernel.names()[def.name];
+                        output.push(format!("removed dependent `{name}`"));
+                    }
+                }
+                Some(["redef"]) => self.redef(input, &mut output)?,
+                Some(["check" | "reduce" | "defeq" | "zfc" | "assumptions"]) => {
+                    self.query(input, &mut output)?
+                }
+                _ if threads.is_none() => {
+                    exact_token(input, "def")?;
+                    self.def(input)?;
+                }
+                _ => {
+                    let start = *batch.get_or_insert(self.kernel.len());
+                    let res = exact_token(input, "def").map_err(Failure::from);
+                    if let Err(e) = res.and_then(|()| self.declare(input, start)) {
+                        return Err(match self.commit(start, threads.unwrap()) {
+                            Ok(()) => e,
+                            Err(errors) => Failure {
+                                message: format!("{}\n{}", errors.message, e.message),
+                                ..e
+                            },
+                        });
+                    }
+                }
+            }
+        }
+        if let (Some(threads), Some(start)) = (threads, batch) {
+            self.commit(start, threads)?;
+        }
+        Ok(output)
+    }
+    fn query(&mut self, input: &mut &str, output: &mut Vec<String>) -> Result<(), Failure> {
+        let command = token(input).unwrap();
+        let e = self.expr(input)?;
+        let r#type = self.kernel.type_of(&e).map_err(checked)?;
+        let display = |e| self.kernel.display(e);
+        let (len, mut sorry) = (output.len(), self.kernel.uses_sorry(&e));
+        match command {
+            "check" => output.push(format!("{} : {}", display(&e), display(&r#type))),
+            "zfc" => {
+                let zfc =
+                    zfc::translate(&self.kernel, &e).and_then(|zfc| zfc.show(zfc::Syntax::Plain));
+                let zfc = zfc.map_err(checked)?;
+                output.push(format!("⟦{}⟧ = {zfc}", display(&e)));
+            }
+            "assumptions" => {
+                let axioms = self.kernel.assumptions(&e).into_iter();
+                let axioms = axioms.map(|(n, native)| {
+                    let name = self.kernel.name_of(n);
+                    match native {
+                        true => format!("{name} (native)"),
+                        false => name.to_owned(),
+                    }
+                });
+                let axioms = axioms.collect::<Vec<_>>();
+                match axioms.is_empty() {
+                    true => output.push(format!("{} depends on no axioms", display(&e))),
+                    false => {
+                        output.push(format!("{} depends on {}", display(&e), axioms.join(", ")))
+                    }
+                }
+            }
+            "reduce" => {
+                let whnf = self.kernel.whnf(&e);
+                let nf = self.kernel.normalize(&e);
+                output.push(format!("{} ⇝ {}", display(&e), display(&whnf)));
+                output.push(format!("{} ⇝* {}", display(&e), display(&nf)));
+            }
+            _ => {
+                exact_token(input, ",")?;
+                let other = self.expr(input)?;
+                self.kernel.type_of(&other).map_err(checked)?;
+                sorry |= self.kernel.uses_sorry(&other);
+                let eq = if self.kernel.def_eq(&e, &other) {
+                    "≡"
+                } else {
+                    "≢"
+                };
+                let display = |e| self.kernel.display(e);
+                output.push(format!("{} {eq} {}", display(&e), display(&other)));
+            }
+        }
+        if sorry {
+            output[len..]
+                .iter_mut()
+                .for_each(|line| line.push_str(" (uses sorry)"));
+        }
+        Ok(exact_token(input, ";")?)
+    }
+    /// Removes the given sorted declarations, which must be closed under dependents,
+    /// returning the removed declarations in order and the new index of each old declaration.
+    #[allow(clippy::type_complexity)]
+    fn remove(
+        &mut self,
+        indices: &[u32],
+    ) -> Result<(Vec<(u32, Def, Vec<u32>)>, Vec<Option<u32>>), String> {
+        let removed = indices.iter().map(|&i| {
+            let def = self.kernel.def(i).clone();
+            (i, def, self.unfolds[i].clone())
+        });
+        let removed: Vec<_> = removed.collect();
+        let map = self.kernel.remove(indices)?;
+
+        self.defs = defs_by_name(&self.kernel);
+        let kept = self
+            .unfolds
+            .iter()
+            .zip(&map)
+            .filter(|(_, new)| new.is_some());
+        self.unfolds = kept
+            .map(|(unfold, _)| {
+                let mut unfold = unfold.clone();
+                unfold.retain_mut(|i| map[*i as usize].map(|new| *i = new).is_some());
+                unfold
+            })
+            .collect();
+        Ok((removed, map))
+    }
+    /// Replaces a declaration, then rechecks everything that depended on it.
+    /// Dependents that no longer typecheck are removed.
+    fn redef(&mut self, input: &mut &str, output: &mut Vec<String>) -> Result<(), Failure> {
+        exact_token(input, "redef")?;
+        let [ident] = peek(input).ok_or("unexpected EOF")?;
+        let ident = ident.strip_suffix(':').ok_or("no trailing colon")?;
+        let n = fvar(self, ident)?;
+
+        let backup = self.clone();
+        let mut removed = self.kernel.dependents(n);
+        removed.insert(0, n);
+        let (removed, mut map) = self.remove(&removed).map_err(checked)?;
+        if let Err(e) = self.def(input) {
+            *self = backup;
+            return Err(e);
+        }
+        map[n as usize] = self.lookup(ident);
+
+        for (old, mut def, mut unfold) in removed.into_iter().skip(1) {
+            let remap = |i: u32| map[i as usize].ok_or(i);
+            let res = (def.r#type.try_map_fvars(remap))
+                .and_then(|()| {
+                    def.value
+                        .iter_mut()
+                        .try_for_each(|v| v.try_map_fvars(remap))
+                })
+                .map_err(|i| format!("depends on removed `{}`", backup.kernel.name_of(i)))
+                .and_then(|()| {
+                    unfold.retain_mut(|i| map[*i as usize].map(|new| *i = new).is_some());
+                    self.insert(&def, unfold, true)
+                });
+            match res {
+                Ok(new) => map[old as usize] = Some(new),
+                Err(e) => {
+                    let name = &self.kernel.names()[def.name];
+                    output.push(format!("removed dependent `{name}`: {e}"));
                 }
-                continue;
             }
-            self.def(&mut input)?;
         }
         Ok(())
     }
-    fn def(&mut self, input: &mut &str) -> Result<(), String> {
-        exact_token(input, "def")?;
+    /// Adds a declaration that was checked in another environment,
+    /// checking it again if `check` is set.
+    pub(crate) fn insert(
+        &mut self,
+        def: &Def,
+        unfold: Vec<u32>,
+        check: bool,
+    ) -> Result<u32, String> {
+        let name = &self.kernel.names()[def.name];
+        if self.named(def.name).is_some() {
+            return Err(format!("duplicate definition `{name}`"));
+        }
+        if check {
+            let name = name.to_owned();
+            self.kernel
+                .timed(&name, |kernel| check_decl(kernel, def, &unfold))?;
+        }
+        let n = self
+            .kernel
+            .add(def.name, def.r#type.clone(), def.value.clone());
+        self.name_def(def.name, n);
+        self.unfolds.push(unfold);
+        Ok(n)
+    }
+    fn def(&mut self, input: &mut &str) -> Result<(), Failure> {
+        let (ident, r#type, value) = self.def_header(input, self.kernel.len())?;
+        let unfolding = &self.unfolding;
+        let unfolded = (self.kernel).timed(ident, |kernel| {
+            check_def(kernel, unfolding, &r#type, &value)
+        });
+        let unfolded = unfolded.map_err(|e| checked(e).within(ident))?;
+        self.def_body(input, ident, r#type, value, unfolded, Self::def)
+    }
+    /// Adds a definition without checking it, to be checked later by [`Self::commit`]
+    /// along with the others from `start` onward.
+    fn declare(&mut self, input: &mut &str, start: u32) -> Result<(), Failure> {
+        let (ident, r#type, value) = self.def_header(input, start)?;
+        let unchecked = value.clone();
+        let inner = move |st: &mut Self, input: &mut &str| st.declare(input, start);
+        self.def_body(input, ident, r#type, value, unchecked, inner)
+    }
+    /// Parses the name, type and value of a definition.
+    /// The declarations before `checked` have been checked.
+    fn def_header<'i>(
+        &self,
+        input: &mut &'i str,
+        checked: u32,
+    ) -> Result<(&'i str, Expr, Expr), Failure> {
         let ident = token(input).ok_or("unexpected EOF")?;
         let ident = ident.strip_suffix(':').ok_or("no trailing colon")?;
-        if self.defs.contains_key(ident) {
-            return Err(format!("duplicate definition `{ident}`"));
+        let within = |e: String| Failure::from(e).within(ident);
+        if ident == "sorry" {
+            return Err(within("`sorry` is reserved".to_owned()));
+        }
+        if self.lookup(ident).is_some() {
+            return Err(within(format!("duplicate definition `{ident}`")));
         }
 
-        let r#type = self.expr(input)?;
-        exact_token(input, ":=")?;
+        let r#type = self.expr(input).map_err(within)?;
+        exact_token(input, ":=").map_err(within)?;
 
-        let value = self.expr(input)?;
-        let mut checker = LEVEL_Z.lam(r#type.clone()).app([value]);
-        checker.visit(0, |_, e| match e {
-            &mut Expr::FVar(n) => e.clone_from(&self.vals[n as usize]),
-            _ => {}
+        let value = match peek(input) {
+            Some(["sorry", ";" | ","]) => self.sorry(input, &r#type, checked).map_err(within)?,
+            _ => self.expr(input).map_err(within)?,
+        };
+        Ok((ident, r#type, value))
+    }
+    /// Parses a value that is only `sorry`, which stands for `sorry u type`,
+    /// where `type` lives in `Sort u`.
+    /// Finding `u` trusts the declarations `type` mentions, so they must be before `checked`.
+    fn sorry(&self, input: &mut &str, r#type: &Expr, checked: u32) -> Result<Expr, String> {
+        exact_token(input, "sorry")?;
+        let sorry = Expr::FVar(fvar(self, "sorry")?);
+        let mut unchecked = None;
+        r#type.for_each_fvar(&mut |n| {
+            unchecked = unchecked.or((checked <= n).then_some(n));
         });
-        self.kernel.type_of(&checker)?;
-        self.vals.push(take(checker.unwrap_app().1));
+        if let Some(n) = unchecked {
+            return Err(format!(
+                "`sorry` at a type that mentions `{}`, which is not checked yet; \
+                write `sorry u type` instead",
+                self.kernel.name_of(n)
+            ));
+        }
+        let sort = self.kernel.whnf(&self.kernel.type_of(r#type)?);
+        match sort {
+            Expr::App(f, level) if *f == SORT => Ok(sorry.app([level, Box::new(r#type.clone())])),
+            _ => Err(format!(
+                "`sorry` at `{}`, which is not in a `Sort`",
+                self.kernel.display(r#type)
+            )),
+        }
+    }
+    fn def_body<F>(
+        &mut self,
+        input: &mut &str,
+        ident: &str,
+        r#type: Expr,
+        value: Expr,
+        unfolded: Expr,
+        inner: F,
+    ) -> Result<(), Failure>
+    where
+        F: Fn(&mut Self, &mut &str) -> Result<(), Failure>,
+    {
+        self.unfolds
+            .push(self.unfolding.iter().map(|&(i, _)| i).collect());
 
-        let (ident, n) = self.kernel.add(ident, r#type);
+        let name = self.kernel.intern(ident);
+        let n = self.kernel.add(name, r#type, Some(value));
         log::info!("added {ident} = {n}");
-        self.defs.insert(ident, n);
+        self.name_def(name, n);
 
         if peek(input) == Some([","]) {
             exact_token(input, ",").unwrap();
             exact_token(input, "with")?;
             exact_token(input, "{")?;
-            while peek(input) != Some(["}"]) {
-                self.def(input)?;
-            }
-            exact_token(input, "}")?;
+            let len = self.unfolding.len();
+            self.unfolding.push((n, unfolded));
+            let res = (|| {
+                while peek(input) != Some(["}"]) {
+                    exact_token(input, "def")?;
+                    inner(self, input)?;
+                }
+                Ok(exact_token(input, "}")?)
+            })();
+            self.unfolding.truncate(len);
+            res.map_err(|e: Failure| e.within(ident))?;
         } else {
-            exact_token(input, ";")?;
+            exact_token(input, ";").map_err(|e| Failure::from(e).within(ident))?;
         }
-        self.vals[n as usize] = Expr::FVar(n);
         Ok(())
     }
+    /// Checks the declarations from `start` onward in parallel,
+    /// then removes those that were rejected.
+    /// The error names the first rejected declaration.
+    fn commit(&mut self, start: u32, threads: usize) -> Result<(), Failure> {
+        let (results, times) = check_parallel(&self.kernel, &self.unfolds, start, threads);
+        for (i, time) in times {
+            let name = self.kernel.name_of(i).to_owned();
+            self.kernel.record_time(&name, time);
+        }
+        let mut rejected = Vec::new();
+        let mut errors = Vec::new();
+        for (i, res) in (start..).zip(results) {
+            if let Err(e) = res {
+                errors.push(format!("`{}`: {e}", self.kernel.name_of(i)));
+                rejected.push(i);
+            }
+        }
+        let Some(&first) = rejected.first() else {
+            return Ok(());
+        };
+        let failure = checked(errors.join("\n")).within(self.kernel.name_of(first));
+        self.remove(&rejected).map_err(checked)?;
+        Err(failure)
+    }
     pub fn axiom(&mut self, mut input: &str) -> Result<(), String> {
         let ident = token(&mut input).ok_or("unexpected EOF")?;
         let ident = ident.strip_suffix(':').ok_or("no trailing colon")?;
         let (r#type, _) = self.check_expr(input)?;
-        let (ident, n) = self.kernel.add(ident, r#type);
-        self.defs.insert(ident, n);
-        self.vals.push(Expr::FVar(n));
+        let name = self.kernel.intern(ident);
+        let n = self.kernel.add(name, r#type, None);
+        self.name_def(name, n);
+        self.unfolds.push(Vec::new());
         Ok(())
     }
-    pub(crate) fn check_expr(&mut self, mut expr: &str) -> Result<(Expr, Expr), String> {
-        let e = self.expr(&mut expr)?;
-        if !expr.is_empty() {
-            return Err("trailing tokens".to_owned());
+    /// Registers a native extension, adding its axiom unless an axiom of that name
+    /// and the same type exists.
+    pub fn extend(&mut self, extension: Arc<dyn Extension>) -> Result<(), String> {
+        let name = extension.name();
+        let (r#type, _) = self.check_expr(extension.r#type())?;
+        let n = match self.lookup(name) {
+            Some(n) if self.kernel.def_eq(&self.kernel.def(n).r#type, &r#type) => n,
+            Some(_) => return Err(format!("`{name}` is declared with another type")),
+            None => {
+                let name = self.kernel.intern(name);
+                let n = self.kernel.add(name, r#type, None);
+                self.name_def(name, n);
+                self.unfolds.push(Vec::new());
+                n
+            }
+        };
+        let constants = extension.constants().iter();
+        let constants = constants.map(|c| fvar(self, c)).collect::<Result<_, _>>()?;
+        self.kernel.extend(n, constants, extension)
+    }
+    pub(crate) fn kernel(&self) -> &kernel::State {
+        &self.kernel
+    }
+    pub(crate) fn lookup(&self, name: &str) -> Option<u32> {
+        self.named(self.kernel.names().get(name)?)
+    }
+    /// The declaration named `name`, if any.
+    fn named(&self, name: Name) -> Option<u32> {
+        self.defs.get(name.index()).copied().flatten()
+    }
+    fn name_def(&mut self, name: Name, n: u32) {
+        while self.defs.len() <= name.index() {
+            self.defs.push(None);
         }
-        let r#type = self.kernel.type_of(&e)?;
-        Ok((e, r#type))
+        self.defs.set(name.index(), Some(n));
+    }
+    /// The declarations directly in `namespace`, in the order their names were first used.
+    /// The empty namespace holds the names without a `:`.
+    pub(crate) fn namespace(&self, namespace: &str) -> impl Iterator<Item = u32> + '_ {
+        let names = self.kernel.names();
+        let namespace = match namespace {
+            "" => Some(Name::ROOT),
+            _ => names.get(namespace),
+        };
+        let members = namespace.into_iter().flat_map(|n| names.members(n));
+        members.filter_map(|name| self.named(name))
+    }
+    /// The name with the given text, for a declaration to [`Self::insert`].
+    pub(crate) fn intern(&mut self, name: &str) -> Name {
+        self.kernel.intern(name)
+    }
+    pub(crate) fn unfold_of(&self, n: u32) -> &[u32] {
+        &self.unfolds[n]
+    }
+    pub(crate) fn check_expr(&self, expr: &str) -> Result<(Expr, Expr), String> {
+        self.check_expr_detailed(expr).map_err(|e| e.message)
+    }
+    /// Like [`Self::check_expr`], but the error says where the expression was rejected.
+    pub(crate) fn check_expr_detailed(&self, input: &str) -> Result<(Expr, Expr), Error> {
+        let mut rest = input;
+        let res = (|| {
+            let e = self.expr(&mut rest)?;
+            if !rest.is_empty() {
+                return Err(Failure::from("trailing tokens"));
+            }
+            let r#type = self.kernel.type_of(&e).map_err(checked)?;
+            Ok((e, r#type))
+        })();
+        res.map_err(|failure| failure.locate(input, rest))
     }
     fn expr(&self, input: &mut &str) -> Result<Expr, String> {
-        let defs = &self.defs;
         let locals = Vec::new();
-        expr(&mut Context { defs, locals }, input)
+        expr(&mut Context { st: self, locals }, input)
+    }
+}
+
+/// An error in the input, before it is located in the whole input.
+struct Failure {
+    message: String,
+    kind: ErrorKind,
+    /// The innermost declaration being added.
+    declaration: Option<String>,
+}
+
+impl Failure {
+    fn within(mut self, ident: &str) -> Self {
+        self.declaration.get_or_insert_with(|| ident.to_owned());
+        self
+    }
+    fn locate(self, input: &str, rest: &str) -> Error {
+        Error {
+            message: self.message,
+            kind: self.kind,
+            declaration: self.declaration,
+            offset: input.len() - rest.len(),
+        }
+    }
+}
+
+/// Errors from parsing are parse errors unless they are marked as [`checked`].
+impl From<String> for Failure {
+    fn from(message: String) -> Self {
+        Self {
+            message,
+            kind: ErrorKind::Parse,
+            declaration: None,
+        }
+    }
+}
+
+impl From<&str> for Failure {
+    fn from(message: &str) -> Self {
+        message.to_owned().into()
+    }
+}
+
+fn checked(message: String) -> Failure {
+    Failure {
+        kind: ErrorKind::Check,
+        ..message.into()
     }
 }
 
 struct Context<'s, 'i> {
-    defs: &'s HashMap<Rc<str>, u32>,
+    st: &'s State,
     locals: Vec<&'i str>,
 }
 
@@ -108,20 +552,24 @@ fn expr<'i>(cx: &mut Context<'_, 'i>, input: &mut &'i str) -> Result<Expr, Strin
     let mut acc: Option<Expr> = None;
     loop {
         let expr = match token(input).ok_or("unexpected EOF")? {
-            i if i.starts_with("Sortω") => Expr::Sortω(number("Sortω", i)?),
-            "∀" => bind(cx, input, |cx, input, l| {
+            i if i.starts_with("Sortω") => Expr::Sortω(number(&i["Sortω".len()..], '₀')?),
+            i if i.starts_with("Sortw") => Expr::Sortω(number(&i["Sortw".len()..], '0')?),
+            "∀" | "forall" => bind(cx, input, |cx, input, l| {
                 exact_token(input, ",")?;
                 Ok(expr(cx, input)?.pi(l))
             })?,
-            "λ" => bind(cx, input, |cx, input, l| {
+            "λ" | "fun" => bind(cx, input, |cx, input, l| {
                 exact_token(input, ",")?;
                 Ok(expr(cx, input)?.lam(l))
             })?,
             "(" => (expr(cx, input)?, exact_token(input, ")")?).0,
             ")" => return Err("unexpected `)`; expected expression".to_owned()),
+            n if n.starts_with(|c: char| c.is_ascii_digit()) => {
+                Expr::Nat(Nat::parse(n).ok_or_else(|| format!("invalid literal `{n}`"))?)
+            }
             v => match cx.locals.iter().rev().position(|&x| x == v) {
                 Some(i) => Expr::BVar(i as u16),
-                None => Expr::FVar(fvar(cx.defs, v)?),
+                None => Expr::FVar(fvar(cx.st, v)?),
             },
         };
         let new_acc = match acc {
@@ -135,8 +583,201 @@ fn expr<'i>(cx: &mut Context<'_, 'i>, input: &mut &'i str) -> Result<Expr, Strin
     }
 }
 
-fn fvar(defs: &HashMap<Rc<str>, u32>, v: &str) -> Result<u32, String> {
-    let res = defs.get(v).copied();
+/// Checks a declaration, first unfolding the enclosing definitions listed in `unfold`.
+fn check_decl(kernel: &kernel::State, def: &Def, unfold: &[u32]) -> Result<(), String> {
+    let Some(value) = &def.value else {
+        return kernel.check_type(&def.r#type);
+    };
+    let mut unfolding = Vec::new();
+    for &i in unfold {
+        let mut val = kernel.def(i).value.clone().unwrap();
+        self::unfold(&unfolding, &mut val);
+        unfolding.push((i, val));
+    }
+    check_def(kernel, &unfolding, &def.r#type, value).map(drop)
+}
+
+/// Checks `value` against `type`, unfolding the definitions in `unfolding`,
+/// and returns the unfolded value.
+fn check_def(
+    kernel: &kernel::State,
+    unfolding: &[(u32, Expr)],
+    r#type: &Expr,
+    value: &Expr,
+) -> Result<Expr, String> {
+    let (mut r#type, mut value) = (r#type.clone(), value.clone());
+    unfold(unfolding, &mut r#type);
+    unfold(unfolding, &mut value);
+    kernel.check(&value, &r#type)?;
+    Ok(value)
+}
+
+fn unfold(unfolding: &[(u32, Expr)], e: &mut Expr) {
+    e.visit(0, |_, e| match e {
+        &mut Expr::FVar(n) => match unfolding.iter().find(|&&(i, _)| i == n) {
+            Some((_, val)) => e.clone_from(val),
+            None => {}
+        },
+        _ => {}
+    });
+}
+
+/// The result of checking each declaration, with the time taken to check those that were checked.
+type Checked = (Vec<Result<(), String>>, Vec<(u32, Duration)>);
+
+/// Checks every declaration from `start` onward on `threads` threads.
+/// A declaration is only checked once everything it depends on has been accepted,
+/// so that the kernel never sees an unchecked type.
+/// Also returns the time taken to check each declaration, in the order they were checked.
+/// If a check panics, the other workers stop and the panic is propagated.
+#[cfg(feature = "std")]
+fn check_parallel(
+    kernel: &kernel::State,
+    unfolds: &Vector<Vec<u32>>,
+    start: u32,
+    threads: usize,
+) -> Checked {
+    struct Schedule {
+        ready: Vec<u32>,
+        waiting_on: Vec<usize>,
+        results: Vec<Option<Result<(), String>>>,
+        times: Vec<(u32, Duration)>,
+        done: usize,
+        /// Whether a worker panicked, so that the others stop instead of waiting on it.
+        aborted: bool,
+    }
+    /// Aborts the schedule if the worker that owns it panics.
+    struct Abort<'a>(&'a Mutex<Schedule>, &'a Condvar);
+    impl Drop for Abort<'_> {
+        fn drop(&mut self) {
+            if thread::panicking() {
+                let mut guard = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+                guard.aborted = true;
+                self.1.notify_all();
+            }
+        }
+    }
+    let len = (kernel.len() - start) as usize;
+    let deps = |i: u32| {
+        let def = kernel.def(i);
+        let deps = def.deps.iter().chain(&unfolds[i]).copied();
+        deps.filter(move |&d| start <= d)
+    };
+    let mut dependents = vec![Vec::new(); len];
+    let mut waiting_on = vec![0; len];
+    for i in start..kernel.len() {
+        for d in deps(i) {
+            dependents[(d - start) as usize].push(i);
+            waiting_on[(i - start) as usize] += 1;
+        }
+    }
+    let ready = (start..kernel.len()).filter(|i| waiting_on[(i - start) as usize] == 0);
+    let schedule = Mutex::new(Schedule {
+        ready: ready.rev().collect(),
+        waiting_on,
+        results: (0..len).map(|_| None).collect(),
+        times: Vec::new(),
+        done: 0,
+        aborted: false,
+    });
+    let condvar = Condvar::new();
+
+    let worker = || {
+        let _abort = Abort(&schedule, &condvar);
+        loop {
+            let mut guard = schedule.lock().unwrap();
+            let i = loop {
+                if guard.aborted {
+                    return;
+                }
+                match guard.ready.pop() {
+                    Some(i) => break i,
+                    None if guard.done == len => return,
+                    None => guard = condvar.wait(guard).unwrap(),
+                }
+            };
+            let rejected = deps(i).find(|&d| guard.results[(d - start) as usize] != Some(Ok(())));
+            drop(guard);
+
+            let (res, time) = match rejected {
+                Some(d) => (
+                    Err(format!("depends on rejected `{}`", kernel.name_of(d))),
+                    None,
+                ),
+                None => {
+                    let def = kernel.def(i);
+                    let (res, time) = kernel::time(|| check_decl(kernel, def, &unfolds[i]));
+                    (res, Some(time))
+                }
+            };
+
+            let mut guard = schedule.lock().unwrap();
+            guard.results[(i - start) as usize] = Some(res);
+            guard.times.extend(time.map(|time| (i, time)));
+            guard.done += 1;
+            for &dependent in &dependents[(i - start) as usize] {
+                let waiting_on = &mut guard.waiting_on[(dependent - start) as usize];
+                *waiting_on -= 1;
+                if *waiting_on == 0 {
+                    guard.ready.push(dependent);
+                }
+            }
+            condvar.notify_all();
+        }
+    };
+    thread::scope(|s| {
+        for _ in 0..threads.max(1) {
+            s.spawn(worker);
+        }
+    });
+
+    let schedule = schedule.into_inner().unwrap();
+    let results = schedule.results.into_iter().map(Option::unwrap).collect();
+    (results, schedule.times)
+}
+
+/// Without threads, checks every declaration from `start` onward in order,
+/// rejecting those that depend on a rejected one as [`check_parallel`] does with threads.
+#[cfg(not(feature = "std"))]
+fn check_parallel(
+    kernel: &kernel::State,
+    unfolds: &Vector<Vec<u32>>,
+    start: u32,
+    _threads: usize,
+) -> Checked {
+    let mut results: Vec<Result<(), String>> = Vec::new();
+    let mut times = Vec::new();
+    for i in start..kernel.len() {
+        let def = kernel.def(i);
+        let mut deps = def.deps.iter().chain(&unfolds[i]).copied();
+        let rejected = deps.find(|&d| start <= d && results[(d - start) as usize].is_err());
+        results.push(match rejected {
+            Some(d) => Err(format!("depends on rejected `{}`", kernel.name_of(d))),
+            None => {
+                let (res, time) = kernel::time(|| check_decl(kernel, def, &unfolds[i]));
+                times.push((i, time));
+                res
+            }
+        });
+    }
+    (results, times)
+}
+
+/// The declaration with each name of `kernel`, by the index of the name.
+fn defs_by_name(kernel: &kernel::State) -> Vector<Option<u32>> {
+    let mut defs = Vec::new();
+    for i in 0..kernel.len() {
+        let name = kernel.def(i).name.index() as usize;
+        if defs.len() <= name {
+            defs.resize(name + 1, None);
+        }
+        defs[name] = Some(i);
+    }
+    Vector::from_iter(defs)
+}
+
+fn fvar(st: &State, v: &str) -> Result<u32, String> {
+    let res = st.lookup(v);
     res.ok_or_else(|| format!("unknown variable `{v}`"))
 }
 
@@ -156,12 +797,12 @@ where
     Ok(r)
 }
 
-fn number(prefix: &str, input: &str) -> Result<u16, String> {
-    let input = input.strip_prefix(prefix).unwrap();
-    input.chars().try_fold(0_u16, |a, v| {
-        let v = u32::from(v).wrapping_sub(u32::from('₀'));
+/// Parses a number whose digits start at `zero`: `'₀'` after `Sortω`, and `'0'` after `Sortw`.
+fn number(input: &str, zero: char) -> Result<u16, String> {
+    input.chars().try_fold(0_u16, |a, c| {
+        let v = u32::from(c).wrapping_sub(u32::from(zero));
         if 10 <= v {
-            return Err(format!("unexpected digit {v}"));
+            return Err(format!("unexpected digit {c}"));
         }
         let a = a.checked_mul(10).and_then(|a| a.checked_add(v as u16));
         a.ok_or_else(|| "number too large".to_owned())
@@ -188,18 +829,76 @@ fn token<'s>(input: &mut &'s str) -> Option<&'s str> {
     if input.is_empty() {
         return None;
     }
+    let end = input.find(punct).unwrap_or(input.len());
+    // A comment ends the token before it.
+    let end = ["//", "/*"]
+        .iter()
+        .filter_map(|c| input[..end].find(c))
+        .fold(end, usize::min);
     let res;
-    (res, *input) = input.split_at(match input.find(punct).unwrap_or(input.len()) {
+    (res, *input) = input.split_at(match end {
         0 => input.chars().next().unwrap().len_utf8(),
         n => n,
     });
-    *input = input.trim_start_matches(['\n', '\t', ' ']);
+    skip_trivia(input);
     Some(res)
 }
 
+/// Skips whitespace, `//` line comments and nestable `/* */` block comments.
+fn skip_trivia(input: &mut &str) {
+    loop {
+        *input = input.trim_start_matches(['\n', '\t', ' ']);
+        if input.starts_with("//") {
+            *input = input.find('\n').map_or("", |i| &input[i..]);
+        } else if input.starts_with("/*") {
+            // An unclosed comment is left in place so that it is reported as an unexpected token.
+            let mut rest = &input["/*".len()..];
+            let mut depth = 1;
+            while depth != 0 {
+                let Some(star) = rest.find('*') else {
+                    return;
+                };
+                let mut skip = "*".len();
+                if star != 0 && rest.as_bytes()[star - 1] == b'/' {
+                    depth += 1;
+                } else if rest.as_bytes().get(star + 1) == Some(&b'/') {
+                    skip = "*/".len();
+                    depth -= 1;
+                }
+                rest = &rest[star + skip..];
+            }
+            *input = rest;
+        } else {
+            break;
+        }
+    }
+}
+
 use crate::expr::Expr;
 use crate::kernel;
 use crate::kernel::builtins::*;
-use std::collections::HashMap;
-use std::mem::take;
-use std::rc::Rc;
+use crate::kernel::Def;
+use crate::log;
+use crate::name::Name;
+use crate::nat::Nat;
+use crate::native::Extension;
+use crate::persistent::Vector;
+use crate::zfc;
+use crate::Error;
+use crate::ErrorKind;
+use crate::SORRY;
+use alloc::borrow::ToOwned;
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::time::Duration;
+#[cfg(feature = "std")]
+use std::sync::Condvar;
+#[cfg(feature = "std")]
+use std::sync::Mutex;
+#[cfg(feature = "std")]
+use std::sync::PoisonError;
+#[cfg(feature = "std")]
+use std::thread;
@@ -0,0 +1,195 @@
+/// A hash trie keyed by `u64`, expanding lazily one nibble at a time.
+struct Trie<T>(Option<Arc<Node<T>>>);
+
+#[derive(Clone)]
+enum Node<T> {
+    Leaf(u64, T),
+    Branch([Option<Arc<Node<T>>>; 16]),
+}
+
+impl<T> Clone for Trie<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T: Clone> Trie<T> {
+    const fn new() -> Self {
+        Self(None)
+    }
+    fn get(&self, key: u64) -> Option<&T> {
+        let mut node = self.0.as_deref()?;
+        let mut shift = 0;
+        loop {
+            match node {
+                Node::Leaf(k, v) => return (*k == key).then_some(v),
+                Node::Branch(children) => node = children[nibble(key, shift)].as_deref()?,
+            }
+            shift += 4;
+        }
+    }
+    fn insert(&mut self, key: u64, value: T) {
+        let mut slot = &mut self.0;
+        let mut shift = 0;
+        loop {
+            match slot.as_deref() {
+                None => return *slot = Some(Arc::new(Node::Leaf(key, value))),
+                Some(&Node::Leaf(k, _)) if k == key => {
+                    return *slot = Some(Arc::new(Node::Leaf(key, value)));
+                }
+                Some(&Node::Leaf(k, _)) => {
+                    let mut children: [_; 16] = Default::default();
+                    children[nibble(k, shift)] = slot.take();
+                    *slot = Some(Arc::new(Node::Branch(children)));
+                }
+                Some(Node::Branch(_)) => {}
+            }
+            let Node::Branch(children) = Arc::make_mut(slot.as_mut().unwrap()) else {
+                unreachable!();
+            };
+            slot = &mut children[nibble(key, shift)];
+            shift += 4;
+        }
+    }
+}
+
+fn nibble(key: u64, shift: u32) -> usize {
+    (key >> shift) as usize & 0xF
+}
+
+pub(crate) struct Vector<T> {
+    trie: Trie<T>,
+    len: u32,
+}
+
+impl<T> Clone for Vector<T> {
+    fn clone(&self) -> Self {
+        let (trie, len) = (self.trie.clone(), self.len);
+        Self { trie, len }
+    }
+}
+
+impl<T: Clone> Vector<T> {
+    pub const fn new() -> Self {
+        Self {
+            trie: Trie::new(),
+            len: 0,
+        }
+    }
+    pub fn len(&self) -> u32 {
+        self.len
+    }
+    pub fn get(&self, i: u32) -> Option<&T> {
+        self.trie.get(u64::from(i))
+    }
+    pub fn set(&mut self, i: u32, value: T) {
+        assert!(i < self.len, "index out of bounds");
+        self.trie.insert(u64::from(i), value);
+    }
+    pub fn push(&mut self, value: T) {
+        self.trie.insert(u64::from(self.len), value);
+        self.len += 1;
+    }
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        (0..self.len).map(|i| &self[i])
+    }
+}
+
+impl<T: Clone> Index<u32> for Vector<T> {
+    type Output = T;
+    fn index(&self, i: u32) -> &T {
+        self.get(i).expect("index out of bounds")
+    }
+}
+
+impl<T: Clone> FromIterator<T> for Vector<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut v = Self::new();
+        iter.into_iter().for_each(|value| v.push(value));
+        v
+    }
+}
+
+pub(crate) struct Map<K, V>(Trie<Vec<(K, V)>>);
+
+impl<K, V> Clone for Map<K, V> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<K, V> Default for Map<K, V> {
+    fn default() -> Self {
+        Self(Trie(None))
+    }
+}
+
+impl<K: Clone + Hash + Eq, V: Clone> Map<K, V> {
+    pub const fn new() -> Self {
+        Self(Trie::new())
+    }
+    pub fn get<Q: ?Sized + Hash + Eq>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+    {
+        let bucket = self.0.get(hash(key))?;
+        bucket
+            .iter()
+            .find(|(k, _)| k.borrow() == key)
+            .map(|(_, v)| v)
+    }
+    pub fn insert(&mut self, key: K, value: V) {
+        let hash = hash(&key);
+        let mut bucket = self.0.get(hash).cloned().unwrap_or_default();
+        bucket.retain(|(k, _)| *k != key);
+        bucket.push((key, value));
+        self.0.insert(hash, bucket);
+    }
+}
+
+impl<K: Clone + Hash + Eq, V: Clone> FromIterator<(K, V)> for Map<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = Self::new();
+        iter.into_iter().for_each(|(k, v)| map.insert(k, v));
+        map
+    }
+}
+
+fn hash<Q: ?Sized + Hash>(key: &Q) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Without `std`, keys are hashed with 64-bit FNV-1a.
+/// Buckets hold colliding keys, so a weaker hash only costs time.
+#[cfg(not(feature = "std"))]
+struct DefaultHasher(u64);
+
+#[cfg(not(feature = "std"))]
+impl DefaultHasher {
+    fn new() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl Hasher for DefaultHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 ^ u64::from(byte)).wrapping_mul(0x100_0000_01b3);
+        }
+    }
+}
+
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::hash::Hash;
+use core::hash::Hasher;
+use core::ops::Index;
+#[cfg(feature = "std")]
+use std::collections::hash_map::DefaultHasher;
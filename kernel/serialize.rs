@@ -0,0 +1,250 @@
+//! The binary format for checked environments.
+//!
+//! All integers are little-endian. A file consists of the magic bytes `KENV`,
+//! the format version as a `u32`, the number of declarations as a `u32`,
+//! and then every non-builtin declaration in order:
+//!
+//! - the name, as a `u32` length followed by UTF-8 bytes;
+//! - a `u8` kind, `0` for axioms and `1` for definitions;
+//! - the type, followed by the value for definitions;
+//! - the enclosing definitions unfolded while checking it, as a `u32` count and `u32` indices;
+//! - the declaration's fingerprint, which is recomputed and compared on import;
+//! - the SHA-256 of the record's bytes above, which also covers the unfolded definitions.
+//!
+//! In paranoid mode, axioms must be among [`crate::AXIOMS`] or be [`crate::SORRY`],
+//! with the same type.
+//!
+//! Expressions are written in prefix order as a `u8` tag followed by their fields:
+//! `0` `FVar(u32)`, `1` `BVar(u16)`, `2` `Sortω(u16)`, `3` `Lam`, `4` `Pi`, `5` `App`,
+//! and `6` for `Nat` literals, as a `u32` length and little-endian bytes without trailing zeros.
+
+const MAGIC: &[u8; 4] = b"KENV";
+const VERSION: u32 = 4;
+/// The deepest expression that is read. Reading does not recurse,
+/// but hashing and checking do, and this bounds the stack they use.
+const MAX_DEPTH: usize = 2048;
+
+pub(crate) fn export(state: &parse::State) -> Vec<u8> {
+    let kernel = state.kernel();
+    let mut out = MAGIC.to_vec();
+    out.extend(VERSION.to_le_bytes());
+    out.extend((kernel.len() - BUILTINS as u32).to_le_bytes());
+    for n in BUILTINS as u32..kernel.len() {
+        let (def, name) = (kernel.def(n), kernel.name_of(n));
+        let start = out.len();
+        out.extend((name.len() as u32).to_le_bytes());
+        out.extend(name.as_bytes());
+        out.push(def.value.is_some().into());
+        write_expr(&mut out, &def.r#type);
+        def.value.iter().for_each(|v| write_expr(&mut out, v));
+        let unfold = state.unfold_of(n);
+        out.extend((unfold.len() as u32).to_le_bytes());
+        unfold.iter().for_each(|i| out.extend(i.to_le_bytes()));
+        out.extend(def.hash);
+        out.extend(checksum(&out[start..]));
+    }
+    out
+}
+
+fn checksum(record: &[u8]) -> [u8; 32] {
+    let mut sha = Sha256::new();
+    sha.update(record);
+    sha.finish()
+}
+
+fn write_expr(out: &mut Vec<u8>, e: &Expr) {
+    match e {
+        Expr::FVar(n) => (out.push(0), out.extend(n.to_le_bytes())).1,
+        Expr::BVar(n) => (out.push(1), out.extend(n.to_le_bytes())).1,
+        Expr::Sortω(n) => (out.push(2), out.extend(n.to_le_bytes())).1,
+        Expr::Nat(n) => {
+            let bytes = n.to_le_bytes();
+            out.push(6);
+            out.extend((bytes.len() as u32).to_le_bytes());
+            out.extend(bytes);
+        }
+        Expr::Lam(l, r) | Expr::Pi(l, r) | Expr::App(l, r) => {
+            out.push(match e {
+                Expr::Lam(..) => 3,
+                Expr::Pi(..) => 4,
+                _ => 5,
+            });
+            write_expr(out, l);
+            write_expr(out, r);
+        }
+    }
+}
+
+/// Loads an exported environment.
+/// Declarations are checked again only if `paranoid` is set;
+/// otherwise they are only checked to be well-scoped, and axioms are trusted.
+/// Those can still be listed with [`crate::Kernel::assumptions`].
+pub(crate) fn import(bytes: &[u8], paranoid: bool) -> Result<parse::State, String> {
+    let mut state = parse::State::new();
+    let mut r = Reader(bytes);
+    if r.take(4)? != MAGIC {
+        return Err("not an exported environment".to_owned());
+    }
+    match r.u32()? {
+        VERSION => {}
+        v => return Err(format!("unsupported format version {v}")),
+    }
+    for _ in 0..r.u32()? {
+        let record = r.0;
+        let name_len = r.u32()?;
+        let name = str::from_utf8(r.take(name_len as usize)?).map_err(|e| e.to_string())?;
+        let kind = r.u8()?;
+        let fvars = state.kernel().len();
+        let r#type = r.expr(fvars)?;
+        let value = match kind {
+            0 => None,
+            1 => Some(r.expr(fvars)?),
+            _ => return Err(format!("`{name}`: invalid declaration kind {kind}")),
+        };
+        let unfold = (0..r.u32()?).map(|_| match r.u32()? {
+            i if i < fvars && state.kernel().def(i).value.is_some() => Ok(i),
+            i => Err(format!("`{name}`: cannot unfold {i}")),
+        });
+        let unfold = unfold.collect::<Result<_, _>>()?;
+
+        let hash_of = |n| state.kernel().def(n).hash;
+        let hash = fingerprint::declaration(name, &r#type, value.as_ref(), hash_of);
+        if hash != r.take(32)? {
+            return Err(format!("`{name}`: hash mismatch"));
+        }
+        let record = &record[..record.len() - r.0.len()];
+        if checksum(record) != r.take(32)? {
+            return Err(format!("`{name}`: checksum mismatch"));
+        }
+
+        if paranoid && value.is_none() {
+            let res = known_axiom(&state, name, &r#type);
+            res.map_err(|e| format!("`{name}`: {e}"))?;
+        }
+
+        let def = Def {
+            name: state.intern(name),
+            r#type,
+            value,
+            deps: Vec::new(),
+            hash,
+            proof: None,
+            sorry: false,
+        };
+        let res = state.insert(&def, unfold, paranoid);
+        res.map_err(|e| format!("`{name}`: {e}"))?;
+    }
+    if !r.0.is_empty() {
+        return Err("trailing bytes".to_owned());
+    }
+    Ok(state)
+}
+
+/// Checks that an axiom is one of [`crate::AXIOMS`] or [`crate::SORRY`] with the same type.
+/// Its type must only mention builtins and axioms, which are known by induction,
+/// so that a definition cannot stand in for an axiom of the same name.
+fn known_axiom(state: &parse::State, name: &str, r#type: &Expr) -> Result<(), String> {
+    let known = AXIOMS.lines().chain([SORRY]);
+    let mut known = known.filter_map(|line| line.split_once(": "));
+    let (_, expected) = known.find(|&(n, _)| n == name).ok_or("unknown axiom")?;
+    let mut axioms = true;
+    r#type.for_each_fvar(&mut |n| axioms &= state.kernel().def(n).value.is_none());
+    match axioms && state.check_expr(expected).is_ok_and(|(e, _)| e == *r#type) {
+        true => Ok(()),
+        false => Err("axiom with an unexpected type".to_owned()),
+    }
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], String> {
+        if self.0.len() < n {
+            return Err("unexpected end of file".to_owned());
+        }
+        let res;
+        (res, self.0) = self.0.split_at(n);
+        Ok(res)
+    }
+    fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+    fn u16(&mut self) -> Result<u16, String> {
+        Ok(u16::from_le_bytes(self.take(2)?.try_into().unwrap()))
+    }
+    fn u32(&mut self) -> Result<u32, String> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+    /// Reads a closed expression that may only refer to the first `fvars` constants.
+    fn expr(&mut self, fvars: u32) -> Result<Expr, String> {
+        // The tags of the enclosing binders and applications, innermost last,
+        // with their left child once it has been read.
+        let mut pending: Vec<(u8, Option<Expr>)> = Vec::new();
+        let mut bvars = 0_u16;
+        loop {
+            let mut e = match self.u8()? {
+                0 => match self.u32()? {
+                    n if n < fvars => Expr::FVar(n),
+                    n => return Err(format!("unknown constant {n}")),
+                },
+                1 => match self.u16()? {
+                    n if n < bvars => Expr::BVar(n),
+                    n => return Err(format!("unbound variable {n}")),
+                },
+                2 => Expr::Sortω(self.u16()?),
+                3..=5 if pending.len() == MAX_DEPTH => return Err("expression too deep".to_owned()),
+                tag @ 3..=5 => {
+                    pending.push((tag, None));
+                    continue;
+                }
+                6 => {
+                    let len = self.u32()?;
+                    let n = Nat::from_le_bytes(self.take(len as usize)?);
+                    Expr::Nat(n.ok_or("malformed literal")?)
+                }
+                tag => return Err(format!("invalid expression tag {tag}")),
+            };
+            // Completes the parents whose right child is `e`.
+            loop {
+                match pending.last_mut() {
+                    None => return Ok(e),
+                    Some((tag, l @ None)) => {
+                        if *tag != 5 {
+                            bvars = bvars.checked_add(1).ok_or("too many binders")?;
+                        }
+                        *l = Some(e);
+                        break;
+                    }
+                    Some((_, Some(_))) => {
+                        let (tag, l) = pending.pop().unwrap();
+                        let l = l.unwrap();
+                        e = match tag {
+                            3 => e.lam(l),
+                            4 => e.pi(l),
+                            _ => l.app([e]),
+                        };
+                        if tag != 5 {
+                            bvars -= 1;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+use crate::expr::Expr;
+use crate::fingerprint;
+use crate::kernel::builtins::BUILTINS;
+use crate::kernel::Def;
+use crate::nat::Nat;
+use crate::parse;
+use crate::sha256::Sha256;
+use crate::AXIOMS;
+use crate::SORRY;
+use alloc::borrow::ToOwned;
+use alloc::format;
+use alloc::string::String;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use core::str;
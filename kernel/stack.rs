@@ -34,6 +34,7 @@ impl<T> Deref for Stack<'_, T> {
     }
 }
 
-use std::marker::PhantomData;
-use std::ops::Deref;
-use std::ptr;
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+use core::ops::Deref;
+use core::ptr;
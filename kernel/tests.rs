@@ -308,6 +308,1177 @@ fn levels() {
     }
 }
 
+#[test]
+fn syntax() {
+    let mut kernel = crate::Kernel::new();
+    kernel
+        .add(
+            "// line comment
+            def id: forall u: Level, forall α: Sort u, forall a: α, α /* block /* nested */ */ :=
+                fun u: Level, fun α: Sort u, fun a: α, a; // trailing
+            def big: Sortw12 := Sortω₁₁;",
+        )
+        .unwrap();
+    // A comment ends the token before it.
+    let output = kernel
+        .add("def t: Bool := true/* c */; check t// c\n;")
+        .unwrap();
+    assert_eq!(output, ["t : Bool"]);
+    kernel
+        .add("def unclosed: Sortw0 := Sortw0; /*")
+        .unwrap_err();
+    typecheck("(λ x: Sortω₁, x) Sortw0").unwrap();
+    typecheck("(λ x: Sortw1, x) Sortω₁").unwrap_err();
+    // Each token uses one spelling.
+    for mixed in ["Sortω1", "Sortw₁", "Sortω₁1", "Sortw1₁"] {
+        let err = typecheck(mixed).unwrap_err();
+        assert!(err.contains("unexpected digit"), "{err}");
+    }
+}
+
+#[test]
+fn queries() {
+    let mut kernel = crate::Kernel::new();
+    let output = kernel
+        .add(
+            "def id: ∀ u: Level, ∀ α: Sort u, ∀ a: α, α := λ u: Level, λ α: Sort u, λ a: α, a;
+            check id;
+            reduce λ b: Bool, (λ x: Bool, x) ((λ x: Bool, x) b);
+            defeq (λ x: Bool, x) true, true;
+            defeq true, false;",
+        )
+        .unwrap();
+    assert_eq!(
+        output,
+        [
+            "id : ∀ _: Level, ∀ _: (Sort _0), ∀ _: _0, _1",
+            "λ _: Bool, (λ _: Bool, _0) ((λ _: Bool, _0) _0) ⇝ λ _: Bool, (λ _: Bool, _0) ((λ _: Bool, _0) _0)",
+            "λ _: Bool, (λ _: Bool, _0) ((λ _: Bool, _0) _0) ⇝* λ _: Bool, _0",
+            "(λ _: Bool, _0) true ≡ true",
+            "true ≢ false",
+        ]
+    );
+    kernel.add("check Sort;").unwrap();
+    kernel.add("check undefined;").unwrap_err();
+    kernel.add("defeq Bool, Level:0 Level:0;").unwrap_err();
+}
+
+#[test]
+fn removal() {
+    let mut kernel = crate::Kernel::new();
+    kernel
+        .add(
+            "def a: Bool := true;
+            def b: Bool := false;
+            def c: Eq (Level:s Level:0) Bool a a := Eq:refl (Level:s Level:0) Bool a;
+            def d: Eq (Level:s Level:0) Bool b b := Eq:refl (Level:s Level:0) Bool b;
+            def e: Bool := a, with {
+                def e_eq: Eq (Level:s Level:0) Bool e a := Eq:refl (Level:s Level:0) Bool a;
+            }
+            def f: Eq (Level:s Level:0) Bool e a := e_eq;",
+        )
+        .unwrap();
+
+    let output = kernel.add("remove a").unwrap();
+    let removed = ["c", "e", "e_eq", "f"].map(|n| format!("removed dependent `{n}`"));
+    assert_eq!(output, removed);
+    kernel.add("check d; check b;").unwrap();
+    kernel.add("check a;").unwrap_err();
+    kernel.add("check f;").unwrap_err();
+
+    kernel
+        .add("def a: Bool := true; def c: Eq (Level:s Level:0) Bool a a := Eq:refl (Level:s Level:0) Bool a;")
+        .unwrap();
+    kernel.add("redef a: Bool := false;").unwrap();
+    kernel.add("check c;").unwrap();
+    let output = kernel.add("redef b: Bool := b;").unwrap_err();
+    assert_eq!(output, "unknown variable `b`");
+    kernel.add("check d;").unwrap();
+
+    kernel
+        .add(
+            "def e: Bool := a, with {
+                def e_eq: Eq (Level:s Level:0) Bool e a := Eq:refl (Level:s Level:0) Bool a;
+            }
+            def g: Bool := e;",
+        )
+        .unwrap();
+    let output = kernel.add("redef e: Bool := true;").unwrap();
+    assert_eq!(output.len(), 1);
+    assert!(output[0].starts_with("removed dependent `e_eq`: type mismatch"));
+    kernel.add("check g;").unwrap();
+    kernel.add("check e_eq;").unwrap_err();
+    kernel.add("redef e: Bool := a, with { def e_eq: Eq (Level:s Level:0) Bool e a := Eq:refl (Level:s Level:0) Bool a; }").unwrap();
+    kernel.add("redef a: Bool := true;").unwrap();
+    kernel.add("check e_eq; check g;").unwrap();
+    kernel.add("remove Level:0").unwrap_err();
+}
+
+#[test]
+fn snapshots() {
+    let mut kernel = crate::Kernel::new();
+    let empty = kernel.snapshot();
+    kernel.add("def a: Bool := true;").unwrap();
+    let with_a = kernel.snapshot();
+
+    kernel.add("def b: Bool := a;").unwrap();
+    kernel.restore(&with_a);
+    kernel.add("check b;").unwrap_err();
+    kernel.add("def b: Bool := false; check a;").unwrap();
+
+    kernel.restore(&empty);
+    kernel.add("check a;").unwrap_err();
+    kernel.restore(&with_a);
+    kernel.add("check a; check b;").unwrap_err();
+}
+
+#[test]
+fn persistent() {
+    use crate::persistent::Map;
+    use crate::persistent::Vector;
+
+    let mut v = (0..1000).collect::<Vector<u32>>();
+    let old = v.clone();
+    v.push(1000);
+    assert!(old.iter().copied().eq(0..1000));
+    assert!(v.iter().copied().eq(0..1001));
+    assert_eq!(old.get(1000), None);
+
+    let mut m = (0..1000)
+        .map(|i| (i.to_string(), i))
+        .collect::<Map<String, u32>>();
+    let old = m.clone();
+    m.insert("0".to_owned(), 1);
+    m.insert("new".to_owned(), 2);
+    assert!((1..1000).all(|i| m.get(&*i.to_string()) == Some(&i)));
+    assert_eq!(m.get("0"), Some(&1));
+    assert_eq!(old.get("0"), Some(&0));
+    assert!(m.get("new").is_some() && old.get("new").is_none());
+}
+
+#[test]
+fn parallel() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<crate::Kernel>();
+    assert_send_sync::<crate::Snapshot>();
+
+    let mut kernel = crate::Kernel::new();
+    let mut input = String::new();
+    for i in 0..32 {
+        input += &format!(
+            "def a{i}: Bool := true;
+            def b{i}: Eq (Level:s Level:0) Bool a{i} a{i} := Eq:refl (Level:s Level:0) Bool a{i};
+            def c{i}: Bool := a{i}, with {{
+                def c{i}_eq: Eq (Level:s Level:0) Bool c{i} a{i} := b{i};
+            }}"
+        );
+    }
+    input += "check c31_eq;";
+    let output = kernel.add_parallel(&input, 4).unwrap();
+    assert_eq!(output.len(), 1);
+
+    let e = kernel
+        .add_parallel(
+            "def x: Bool := Bool;
+            def y: Bool := x;
+            def z: Bool := a0;
+            def w: Sortω₀ := Bool, with { def w_eq: Bool := w; }",
+            4,
+        )
+        .unwrap_err();
+    let mut lines = e.lines();
+    assert!(lines.next().unwrap().starts_with("`x`: type mismatch"));
+    assert!(lines.any(|l| l == "`y`: depends on rejected `x`"));
+    assert!(lines.any(|l| l == "`w_eq`: depends on rejected `w`"));
+    kernel.add("check z;").unwrap();
+    kernel.add("check x;").unwrap_err();
+    kernel.add("check w;").unwrap_err();
+
+    let e = kernel
+        .add_parallel("def p: Bool := true; def q: Bool := undefined;", 2)
+        .unwrap_err();
+    assert_eq!(e, "unknown variable `undefined`");
+    kernel.add("check p;").unwrap();
+    kernel.add("check q;").unwrap_err();
+
+    // A panic in one worker is propagated instead of leaving the others waiting.
+    struct Panics;
+    impl crate::Extension for Panics {
+        fn name(&self) -> &str {
+            "Nat:panics"
+        }
+        fn r#type(&self) -> &str {
+            "∀ n: Nat, Nat"
+        }
+        fn arity(&self) -> usize {
+            1
+        }
+        fn reduce(&self, _: &[crate::Term]) -> Option<crate::Term> {
+            panic!("extension panicked")
+        }
+    }
+    kernel.extend(std::sync::Arc::new(Panics)).unwrap();
+    let mut input =
+        "def r: Eq (Level:s Level:0) Nat (Nat:panics 1) 0 := Eq:refl (Level:s Level:0) Nat 0;"
+            .to_owned();
+    for i in 0..8 {
+        input += &format!("def r{i}: Eq (Level:s Level:0) Nat (Nat:panics 1) 0 := r;");
+    }
+    let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        kernel.add_parallel(&input, 4)
+    }));
+    assert!(res.is_err());
+}
+
+#[test]
+fn sha256() {
+    use crate::sha256::Sha256;
+    let hex = |data: &[u8]| {
+        let mut hasher = Sha256::new();
+        hasher.update(data);
+        let digest = hasher.finish();
+        digest
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect::<String>()
+    };
+    assert_eq!(
+        hex(b""),
+        "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+    );
+    assert_eq!(
+        hex(b"abc"),
+        "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+    );
+    assert_eq!(
+        hex(&[b'a'; 1000]),
+        "41edece42d63e8d9bf515a9ba6932e1c20cbc9f5a5d134645adb5db1b9737ea3"
+    );
+}
+
+#[test]
+fn serialize() {
+    use crate::expr::Expr;
+    use crate::kernel::builtins::*;
+    use crate::kernel::Def;
+    use crate::Kernel;
+
+    let mut kernel = Kernel::new();
+    kernel
+        .add(
+            "def a: Bool := true, with {
+                def a_eq: Eq (Level:s Level:0) Bool a true := Eq:refl (Level:s Level:0) Bool true;
+            }",
+        )
+        .unwrap();
+    let bytes = kernel.export();
+    assert_eq!(bytes, kernel.export());
+    for paranoid in [false, true] {
+        let mut imported = Kernel::import(&bytes, paranoid).unwrap();
+        assert_eq!(imported.export(), bytes);
+        imported.add("check a_eq;").unwrap();
+        imported.add("redef a: Bool := true;").unwrap();
+    }
+
+    let mut corrupted = bytes.clone();
+    corrupted[20] ^= 1;
+    assert!(Kernel::import(&corrupted, false).is_err());
+    assert!(Kernel::import(&bytes[..bytes.len() - 1], false).is_err());
+    assert!(Kernel::import(b"KENV\x05\0\0\0", false).is_err());
+    // The unfolded definitions are not part of the fingerprint, but of the checksum.
+    let mut tampered = bytes.clone();
+    let unfold = tampered.len() - 64 - 8;
+    tampered.splice(unfold..unfold + 8, 0_u32.to_le_bytes());
+    let e = Kernel::import(&tampered, false).err().unwrap();
+    assert_eq!(e, "`a_eq`: checksum mismatch");
+
+    let mut bogus = crate::parse::State::new();
+    let def = Def {
+        name: bogus.intern("bogus"),
+        r#type: LEVEL,
+        value: Some(Expr::Sortω(0)),
+        deps: Vec::new(),
+        hash: [0; 32],
+        proof: None,
+        sorry: false,
+    };
+    bogus.insert(&def, Vec::new(), false).unwrap();
+    let bytes = crate::serialize::export(&bogus);
+    Kernel::import(&bytes, false).unwrap();
+    assert!(Kernel::import(&bytes, true).is_err());
+
+    // Axioms must be the kernel's own, with the same type and over the same constants.
+    let export = |defs: &str, axiom: &str| {
+        let mut state = crate::parse::State::new();
+        state.parse(defs).unwrap();
+        state.axiom(axiom).unwrap();
+        crate::serialize::export(&state)
+    };
+    let bytes = export("", "False: Sort Level:0");
+    Kernel::import(&bytes, false).unwrap();
+    let e = Kernel::import(&bytes, true).err().unwrap();
+    assert_eq!(e, "`False`: unknown axiom");
+    let bytes = export("", "Eq: Sort Level:0");
+    let e = Kernel::import(&bytes, true).err().unwrap();
+    assert_eq!(e, "`Eq`: axiom with an unexpected type");
+    let bytes = export(
+        "def Eq: ∀ u: Level, ∀ α: Sort u, ∀ a: α, ∀ b: α, Sort Level:0 :=
+            λ u: Level, λ α: Sort u, λ a: α, λ b: α, ∀ P: Sort Level:0, P;",
+        crate::AXIOMS.lines().nth(1).unwrap(),
+    );
+    let e = Kernel::import(&bytes, true).err().unwrap();
+    assert_eq!(e, "`Eq:refl`: axiom with an unexpected type");
+
+    // Deeply nested expressions are rejected instead of overflowing the stack.
+    let mut deep = b"KENV\x04\0\0\0\x01\0\0\0\x01\0\0\0a\0".to_vec();
+    deep.resize(deep.len() + (1 << 20), 5);
+    let e = Kernel::import(&deep, false).err().unwrap();
+    assert_eq!(e, "expression too deep");
+}
+
+#[test]
+fn fingerprint() {
+    use crate::Kernel;
+
+    let run = |s: &str| {
+        let mut kernel = Kernel::new();
+        kernel.add(s).unwrap();
+        kernel
+    };
+    let a = run("def x: Bool := true; def y: Bool := false; def z: Bool := x;");
+    let b = run("def y: Bool := false; def x: Bool := true; def z: Bool := x;");
+    assert_eq!(a.fingerprint(), b.fingerprint());
+    assert_eq!(a.fingerprint_of("z"), b.fingerprint_of("z"));
+    assert_eq!(a.fingerprint_of("w"), None);
+
+    let c = run("def x: Bool := false; def y: Bool := false; def z: Bool := x;");
+    assert_ne!(a.fingerprint(), c.fingerprint());
+    assert_eq!(a.fingerprint_of("y"), c.fingerprint_of("y"));
+    assert_ne!(a.fingerprint_of("z"), c.fingerprint_of("z"));
+
+    let mut d = run("def x: Bool := false; def y: Bool := false; def z: Bool := x;");
+    d.add("redef x: Bool := true;").unwrap();
+    assert_eq!(a.fingerprint(), d.fingerprint());
+    d.add("remove y").unwrap();
+    assert_ne!(a.fingerprint(), d.fingerprint());
+    assert_eq!(a.fingerprint_of("z"), d.fingerprint_of("z"));
+
+    let imported = Kernel::import(&a.export(), false).unwrap();
+    assert_eq!(a.fingerprint(), imported.fingerprint());
+    assert_ne!(Kernel::new().fingerprint(), a.fingerprint());
+}
+
+#[test]
+fn lean() {
+    use crate::Kernel;
+
+    for &(lean, mapped) in crate::lean::MAPPINGS {
+        let res = Kernel::new().0.check_expr(mapped);
+        assert!(res.is_ok(), "{lean}: {res:?}");
+    }
+
+    // `Eq`, `Eq.refl`, `id`, a theorem `id_eq : id a = a`, `Nat` and a definition using `Nat`.
+    let export = "\
+0.1.2
+1 #NS 0 Eq
+2 #NS 1 refl
+3 #NS 0 u
+4 #NS 0 α
+5 #NS 0 a
+6 #NS 0 b
+7 #NS 0 id
+8 #NS 0 id_eq
+9 #NS 0 Nat
+10 #NS 0 zero
+1 #UP 3
+2 #US 0
+0 #ES 1
+1 #EV 0
+2 #EV 1
+3 #ES 0
+4 #EP #BD 6 2 3
+5 #EP #BD 5 1 4
+6 #EP #BI 4 0 5
+#IND 1 6 0 0 2 1 1 1 1 2 3
+7 #EC 1 1
+8 #EA 7 2
+9 #EA 8 1
+10 #EA 9 1
+11 #EP #BD 5 1 10
+12 #EP #BI 4 0 11
+#CTOR 2 12 1 0 2 0 3
+13 #EP #BD 5 1 2
+14 #EP #BI 4 0 13
+15 #EL #BD 5 1 1
+16 #EL #BI 4 0 15
+#DEF 7 14 16 R 1 3
+17 #EC 7 1
+18 #EA 17 2
+19 #EA 18 1
+20 #EA 8 19
+21 #EA 20 1
+22 #EP #BD 5 1 21
+23 #EP #BI 4 0 22
+24 #EC 2 1
+25 #EA 24 2
+26 #EA 25 1
+27 #EL #BD 5 1 26
+28 #EL #BI 4 0 27
+#THM 8 23 28 3
+29 #ES 2
+#IND 9 29 1 0 0 0 1 9 0
+30 #EC 9
+31 #ELN 0
+#DEF 10 30 31 A
+";
+    let mut kernel = Kernel::new();
+    assert_eq!(
+        kernel.import_lean(export).unwrap(),
+        [
+            "mapped `Eq` to `Eq`",
+            "mapped `Eq.refl` to `Eq:refl`",
+            "`Nat`: unsupported: inductive types",
+            "`zero`: depends on skipped `Nat`",
+            "imported 2 declarations, skipped 2",
+        ]
+    );
+    assert_eq!(
+        kernel.add("check id_eq;").unwrap(),
+        ["id_eq : ∀ _: Level, ∀ _: (Sort _0), ∀ _: _0, Eq _2 _1 (id _2 _1 _0) _0"]
+    );
+
+    // Without unfolding `id`, the theorem does not check.
+    let opaque = export.replace("#DEF 7", "#THM 7").replace(" R 1 3", " 3");
+    let report = Kernel::new().import_lean(&opaque).unwrap();
+    assert!(report[2].starts_with("`id_eq`: "), "{report:?}");
+
+    let fingerprint = kernel.fingerprint();
+    assert!(kernel.import_lean("1 #NS 0 x\n2 #XX").is_err());
+    assert!(kernel.import_lean("#AX 1 0").is_err());
+    assert_eq!(kernel.fingerprint(), fingerprint);
+}
+
+#[test]
+fn dedukti() {
+    use crate::Kernel;
+
+    let mut kernel = Kernel::new();
+    kernel
+        .add(
+            "def not: ∀ b: Bool, Bool := λ b: Bool, Bool:elim (Level:s Level:0) (λ b: Bool, Bool) true false b;
+            def id: ∀ u: Level, ∀ α: Sort u, ∀ a: α, α := λ u: Level, λ α: Sort u, λ a: α, a;",
+        )
+        .unwrap();
+    let dk = kernel.export_dedukti().unwrap();
+    assert!(dk.contains("\n{|Bool|} : El (sort (s (s z))) (Sort (s z)).\n"));
+    assert!(dk.contains(
+        "\ndef {|not|} : El (sort ((imax (s z)) (s z))) \
+            (pi (sort (s z)) (sort (s z)) {|Bool|} (x0 : El (sort (s z)) {|Bool|} => {|Bool|})) := \
+            (x0 : El (sort (s z)) {|Bool|} => ((((({|Bool:elim|} (s z)) \
+            (x1 : El (sort (s z)) {|Bool|} => {|Bool|})) {|true|}) {|false|}) x0)).\n"
+    ));
+    // `id`'s type quantifies over a level that its codomain's level depends on.
+    let id = dk.lines().find(|l| l.starts_with("def {|id|}")).unwrap();
+    assert!(id.starts_with("def {|id|} : El (sortw nz) (pid (sort (s z)) Level "));
+    // dkcheck only accepts ASCII identifiers outside of `{|…|}` quotes.
+    let unquoted: String = dk
+        .split("{|")
+        .map(|s| s.split_once("|}").map_or(s, |s| s.1))
+        .collect();
+    assert!(unquoted.is_ascii());
+    let tokens = unquoted.split(|c: char| c.is_whitespace() || "():,.[]=>-".contains(c));
+    assert!(tokens
+        .flat_map(str::chars)
+        .all(|c| c.is_ascii_alphanumeric() || c == '_'));
+
+    kernel.add("def x1: Bool := true;").unwrap();
+    assert!(kernel.export_dedukti().is_err());
+}
+
+#[test]
+fn zfc() {
+    use crate::Kernel;
+
+    let mut kernel = Kernel::new();
+    kernel
+        .add(
+            "def not: ∀ b: Bool, Bool := λ b: Bool, Bool:elim (Level:s Level:0) (λ b: Bool, Bool) true false b;
+            def id: ∀ u: Level, ∀ α: Sort u, ∀ a: α, α := λ u: Level, λ α: Sort u, λ a: α, a;",
+        )
+        .unwrap();
+    assert_eq!(
+        kernel.zfc("not").unwrap(),
+        "(x0 ∈ ({∅} ∪ {{∅}}) ↦ if(x0 = ∅, {∅}, ∅))"
+    );
+    assert_eq!(
+        kernel.zfc_metamath("not").unwrap(),
+        "( x0 e. ( { (/) } u. { { (/) } } ) |-> if ( x0 = (/) , { (/) } , (/) ) )"
+    );
+    // Quantifying over levels is large, so it translates to a function on sets.
+    assert_eq!(
+        kernel.zfc("id").unwrap(),
+        "λ x0. (x1 ∈ 𝒰(x0) ↦ (x2 ∈ x1 ↦ x2))"
+    );
+    assert!(kernel.zfc_metamath("id").is_err());
+    assert_eq!(
+        kernel.zfc("Eq (Level:s Level:0) Bool true").unwrap(),
+        "(x0 ∈ ({∅} ∪ {{∅}}) ↦ {x1 ∈ {∅} | {∅} = x0})"
+    );
+    assert_eq!(
+        kernel.zfc("λ u: Level, Sigma u").unwrap(),
+        "λ x0. (x1 ∈ 𝒰(x0) ↦ (x2 ∈ (∏ x3 ∈ x1. 𝒰(x0)) ↦ (∑ x4 ∈ x1. x2(x4))))"
+    );
+    assert!(kernel
+        .zfc("W:elim")
+        .unwrap()
+        .contains("(ι x34. ∀x35. (x35 ⊆ (∑ x36 ∈ "));
+    assert!(kernel.zfc("Sortω₀").is_err());
+    assert_eq!(
+        kernel.add("zfc Eq:refl (Level:s Level:0) Bool;").unwrap(),
+        ["⟦Eq:refl (Level:s Level:0) Bool⟧ = (x0 ∈ ({∅} ∪ {{∅}}) ↦ ∅)"]
+    );
+}
+
+#[test]
+fn certificate() {
+    use crate::Kernel;
+
+    let mut kernel = Kernel::new();
+    // Needs the level solver to compare `Sort (Level:max u u)` with `Sort u`.
+    let levels = "λ u: Level, λ A: Sort (Level:max u u), (λ B: Sort u, B) A";
+    let cert = kernel.certify(levels).unwrap();
+    assert!(cert.contains("(defeq level "));
+    assert_eq!(
+        kernel.check_certificate(&cert).unwrap(),
+        "λ _: Level, λ _: (Sort (Level:max _0 _0)), (λ _: (Sort _1), _0) _0 : \
+            ∀ _: Level, ∀ _: (Sort (Level:max _0 _0)), Sort _1"
+    );
+    // Needs proof irrelevance to compare `P h₁` with `P h₂`.
+    let proofs = "λ P: (∀ h: Inhabited (Level:s Level:0) Bool, Sort (Level:s Level:0)),
+        λ h₁: Inhabited (Level:s Level:0) Bool, λ h₂: Inhabited (Level:s Level:0) Bool,
+        λ x: P h₁, (λ y: P h₂, y) x";
+    let cert = kernel.certify(proofs).unwrap();
+    assert!(cert.contains("(defeq uip "));
+    kernel.check_certificate(&cert).unwrap();
+
+    let forged = cert.replacen("(defeq uip ", "(defeq atom ", 1);
+    assert!(kernel.check_certificate(&forged).is_err());
+    let forged = cert.replacen("(c 5)", "(c 6)", 1);
+    assert!(kernel.check_certificate(&forged).is_err());
+
+    // The checker reduces projections and literals with its own rules.
+    let pair = "Sigma:mk (Level:s Level:0) Nat (λ n: Nat, Nat) 2 3";
+    let fst = format!("Sigma:fst (Level:s Level:0) Nat (λ n: Nat, Nat) ({pair})");
+    let projection = format!(
+        "(λ h: Eq (Level:s Level:0) Nat (Nat:add 1 1) 2, h) (Eq:refl (Level:s Level:0) Nat ({fst}))"
+    );
+    let cert = kernel.certify(&projection).unwrap();
+    kernel.check_certificate(&cert).unwrap();
+
+    kernel.add("def b: Bool := true;").unwrap();
+    assert!(kernel.check_certificate(&cert).is_err());
+}
+
+#[test]
+fn proof_irrelevance() {
+    let kernel = crate::Kernel::new();
+    let proof = |name| kernel.0.kernel().def(kernel.0.lookup(name).unwrap()).proof;
+    assert_eq!(proof("Level:s"), Some((1, false)));
+    assert_eq!(proof("Eq"), Some((4, false)));
+    assert_eq!(proof("Eq:refl"), Some((3, true)));
+    assert_eq!(proof("funext"), Some((7, true)));
+    // The motive's level is an argument.
+    assert_eq!(proof("Eq:elim"), None);
+
+    let mut kernel = crate::Kernel::new();
+    let output = kernel
+        .add(
+            "defeq Inhabited:mk (Level:s Level:0) Bool true, Inhabited:mk (Level:s Level:0) Bool false;
+            defeq λ h: Inhabited (Level:s Level:0) Bool, h, λ h: Inhabited (Level:s Level:0) Bool,\
+                Inhabited:mk (Level:s Level:0) Bool true;
+            defeq λ b: Bool, b, λ b: Bool, true;
+            defeq Eq:refl (Level:s Level:0) Bool true, Eq:refl (Level:s Level:0) Bool true;",
+        )
+        .unwrap();
+    assert!(output[0].contains(" ≡ "));
+    assert!(output[1].contains(" ≡ "));
+    assert!(output[2].contains(" ≢ "));
+    assert!(output[3].contains(" ≡ "));
+}
+
+#[test]
+fn caches() {
+    let mut kernel = crate::Kernel::new();
+    let axioms = kernel.stats();
+    kernel
+        .add(
+            "def swap: ∀ u: Level, ∀ v: Level, ∀ α: Sort (Level:max u v), Sort (Level:max v u) :=
+                λ u: Level, λ v: Level, λ α: Sort (Level:max u v), α;",
+        )
+        .unwrap();
+    let stats = kernel.stats();
+    assert!(stats.type_of_misses > axioms.type_of_misses);
+    assert!(stats.def_eq_hits > axioms.def_eq_hits);
+    assert!((0.0..1.0).contains(&stats.type_of_hit_rate()));
+    assert!((0.0..1.0).contains(&stats.def_eq_hit_rate()));
+
+    // Snapshots keep a copy of the counters, so restoring one rolls them back.
+    let snapshot = kernel.snapshot();
+    kernel.add("check swap Level:0;").unwrap();
+    assert!(kernel.stats().type_of_misses > stats.type_of_misses);
+    kernel.restore(&snapshot);
+    assert_eq!(kernel.stats(), stats);
+}
+
+#[test]
+fn profiling() {
+    let mut kernel = crate::Kernel::new();
+    assert!(kernel.times().is_empty());
+    let empty = kernel.snapshot();
+    let axioms = kernel.stats();
+    kernel
+        .add(
+            "def id: ∀ u: Level, ∀ α: Sort u, ∀ a: α, α := λ u: Level, λ α: Sort u, λ a: α, a;
+            def swap: ∀ u: Level, ∀ v: Level, Sort (Level:s (Level:max u v)) :=
+                λ u: Level, λ v: Level,
+                id (Level:s (Level:s (Level:max u v))) (Sort (Level:s (Level:max v u))) (Sort (Level:max v u));
+            def prop: (λ u: Level, Sort (Level:s u)) Level:0 := Sort Level:0;",
+        )
+        .unwrap();
+    let stats = kernel.stats();
+    assert!(stats.whnf_calls > axioms.whnf_calls);
+    assert!(stats.beta_reductions > axioms.beta_reductions);
+    assert!(stats.def_eq_calls >= stats.def_eq_hits + stats.def_eq_misses);
+    assert!(stats.level_solves > axioms.level_solves);
+    assert!(stats.level_vars > axioms.level_vars);
+    assert!(stats.max_depth >= 4);
+
+    // Only small terms are cached, so a deep term is not cloned at every level.
+    let deep = "Nat:succ (".repeat(300) + "Nat:zero" + &")".repeat(300);
+    kernel.add(&format!("check {deep};")).unwrap();
+    let misses = kernel.stats().type_of_misses - stats.type_of_misses;
+    assert!(misses <= crate::kernel::CACHED_SIZE as u64, "{misses}");
+
+    let times = kernel.times();
+    if !cfg!(feature = "std") {
+        return assert!(times.is_empty());
+    }
+    let names: Vec<_> = times.iter().map(|(name, _)| &**name).collect();
+    assert_eq!(names, ["id", "swap", "prop"]);
+    kernel.restore(&empty);
+    assert!(kernel.times().is_empty());
+    assert_eq!(kernel.stats(), axioms);
+
+    // Only the most recent times are kept.
+    let mut state = crate::parse::State::new().kernel().clone();
+    let max = crate::kernel::MAX_TIMES;
+    for i in 0..max + 2 {
+        state.record_time(&i.to_string(), core::time::Duration::ZERO);
+    }
+    let times = state.times();
+    assert_eq!(times.len(), max as usize);
+    assert_eq!(&*times[0].0, "2");
+    assert_eq!(*times.last().unwrap().0, *(max + 1).to_string());
+}
+
+#[test]
+fn checking() {
+    let mut kernel = crate::Kernel::new();
+    let e = kernel
+        .add(
+            "def const: ∀ α: Sort Level:0, ∀ β: Sort Level:0, ∀ a: α, β :=
+                λ α: Sort Level:0, λ β: Sort Level:0, λ a: α, a;",
+        )
+        .unwrap_err();
+    // The expected type is pushed under the binders, so the mismatch is reported at `a`.
+    assert_eq!(e, "type mismatch in `_0`:\nexpected _1\n   found _2");
+    let e = kernel
+        .add("def level: ∀ u: Level, Sort u := λ u: Sort Level:0, Sort Level:0;")
+        .unwrap_err();
+    assert!(e.starts_with("binder type mismatch"));
+    let e = kernel
+        .add("def refl: Sort Level:0 := Eq:refl (Level:s Level:0) (Sort Level:0) Level:0;")
+        .unwrap_err();
+    assert_eq!(
+        e,
+        "type mismatch in `Level:0`:\nexpected Sort Level:0\n   found Level"
+    );
+}
+
+#[test]
+fn nat() {
+    use crate::expr::Expr;
+    use crate::nat::Nat;
+
+    let mut kernel = crate::Kernel::new();
+    let output = kernel
+        .add(
+            "reduce Nat:add 1000 1000;
+            reduce Nat:mul 123456789012345678901234567890 987654321098765432109876543210;
+            reduce Nat:sub 3 5;
+            reduce Nat:div 7 0;
+            reduce Nat:mod 7 0;
+            reduce Nat:beq 2 (Nat:succ (Nat:succ Nat:zero));
+            reduce Nat:ble 3 2;
+            defeq Nat:succ (Nat:succ Nat:zero), 2;
+            defeq λ n: Nat, Nat:succ (Nat:succ n), λ n: Nat, Nat:succ (Nat:succ n);
+            defeq 3, Nat:succ 3;",
+        )
+        .unwrap();
+    let whnfs: Vec<_> = output
+        .iter()
+        .step_by(2)
+        .map(|line| line.split(" ⇝ ").nth(1))
+        .collect();
+    assert_eq!(
+        whnfs[..7],
+        [
+            Some("2000"),
+            Some("121932631137021795226185032733622923332237463801111263526900"),
+            Some("0"),
+            Some("0"),
+            Some("7"),
+            Some("true"),
+            Some("false"),
+        ]
+    );
+    assert_eq!(output[14], "Nat:succ (Nat:succ Nat:zero) ≡ 2");
+    assert_eq!(output[16], "3 ≢ Nat:succ 3");
+
+    kernel
+        .add(
+            "def sum: Eq (Level:s Level:0) Nat (Nat:add 1000 1000) 2000 :=
+                Eq:refl (Level:s Level:0) Nat 2000;
+            def succ_elim: ∀ motive: (∀ n: Nat, Sort Level:0), ∀ z: motive 0,
+                ∀ s: (∀ n: Nat, ∀ r: motive n, motive (Nat:succ n)),
+                Eq Level:0 (motive 5) (Nat:elim Level:0 motive z s 5) (s 4 (Nat:elim Level:0 motive z s 4)) :=
+                λ motive: (∀ n: Nat, Sort Level:0), λ z: motive 0,
+                λ s: (∀ n: Nat, ∀ r: motive n, motive (Nat:succ n)), Nat:succ_elim Level:0 motive z s 4;",
+        )
+        .unwrap();
+    kernel
+        .add("def wrong: Eq (Level:s Level:0) Nat (Nat:add 2 2) 5 := Eq:refl (Level:s Level:0) Nat 4;")
+        .unwrap_err();
+
+    let certificate = kernel
+        .certify("Eq:refl (Level:s Level:0) Nat (Nat:add 2 2)")
+        .unwrap();
+    assert!(kernel.check_certificate(&certificate).is_ok());
+    let bytes = kernel.export();
+    let imported = crate::Kernel::import(&bytes, true).unwrap();
+    assert_eq!(imported.export(), bytes);
+
+    assert_eq!(
+        kernel.zfc("2").unwrap(),
+        kernel.zfc("Nat:succ (Nat:succ Nat:zero)").unwrap()
+    );
+    assert!(kernel.zfc("100").is_err());
+
+    // The recursion equations hold definitionally on literals.
+    let state = &kernel.0;
+    for (axiom, args) in [
+        ("Nat:add_zero", "5"),
+        ("Nat:add_succ", "7 2"),
+        ("Nat:sub_zero", "5"),
+        ("Nat:zero_sub", "3"),
+        ("Nat:succ_sub_succ", "2 7"),
+        ("Nat:mul_zero", "5"),
+        ("Nat:mul_succ", "7 2"),
+        ("Nat:div_zero", "7"),
+        ("Nat:div_lt", "2 7 (Eq:refl (Level:s Level:0) Bool true)"),
+        ("Nat:div_add", "7 2"),
+        ("Nat:mod_zero", "7"),
+        ("Nat:mod_lt", "2 7 (Eq:refl (Level:s Level:0) Bool true)"),
+        ("Nat:mod_add", "7 2"),
+        ("Nat:zero_beq_zero", ""),
+        ("Nat:zero_beq_succ", "3"),
+        ("Nat:succ_beq_zero", "0"),
+        ("Nat:succ_beq_succ", "3 3"),
+        ("Nat:zero_ble", "0"),
+        ("Nat:succ_ble_zero", "5"),
+        ("Nat:succ_ble_succ", "7 2"),
+    ] {
+        let (_, r#type) = state.check_expr(&format!("{axiom} {args}")).unwrap();
+        let Expr::App(f, rhs) = r#type else { panic!() };
+        let Expr::App(_, lhs) = *f else { panic!() };
+        assert!(state.kernel().def_eq(&lhs, &rhs), "{axiom} {args}");
+    }
+
+    // Literals depend on `Nat`.
+    let output = kernel.add("def three: Nat := 3; remove Nat").unwrap();
+    assert!(output
+        .iter()
+        .any(|line| line == "removed dependent `three`"));
+    kernel.add("check 3;").unwrap_err();
+    let lt = "Nat:div_lt 7 2 (Eq:refl (Level:s Level:0) Bool true)";
+    crate::Kernel::new()
+        .add(&format!("check {lt};"))
+        .unwrap_err();
+
+    // Division by a number with more than one digit in base 2³².
+    let (a, b) = (
+        "121932631137021795226185032733622923332237463801111263526900",
+        "123456789012345678901234567897",
+    );
+    let (a, b) = (Nat::parse(a).unwrap(), Nat::parse(b).unwrap());
+    let (q, r) = a.div_rem(&b);
+    assert_eq!(q.to_string(), "987654321098765432109876543153");
+    assert_eq!(r.to_string(), "123456726012345672601234567659");
+    assert_eq!(q.mul(&b).add(&r), a);
+}
+
+#[test]
+fn extension() {
+    use crate::Extension;
+    use crate::Nat;
+    use crate::Term;
+    use std::sync::Arc;
+
+    struct Even;
+    impl Extension for Even {
+        fn name(&self) -> &str {
+            "Nat:even"
+        }
+        fn r#type(&self) -> &str {
+            "∀ n: Nat, Bool"
+        }
+        fn arity(&self) -> usize {
+            1
+        }
+        fn constants(&self) -> &[&str] {
+            &["false", "true"]
+        }
+        fn reduce(&self, args: &[Term]) -> Option<Term> {
+            let Term::Nat(n) = &args[0] else {
+                return None;
+            };
+            let rem = n.div_rem(&Nat::from(2)).1;
+            Some(Term::Const(rem.is_zero().into()))
+        }
+    }
+
+    let mut kernel = crate::Kernel::new();
+    kernel.extend(Arc::new(Even)).unwrap();
+    assert!(kernel.extend(Arc::new(Even)).is_err());
+    let output = kernel
+        .add(
+            "reduce Nat:even (Nat:add 20 1);
+            reduce λ n: Nat, Nat:even n;
+            def even: Eq (Level:s Level:0) Bool (Nat:even 1000) true :=
+                Eq:refl (Level:s Level:0) Bool true;
+            assumptions even;",
+        )
+        .unwrap();
+    assert_eq!(output[0], "Nat:even (Nat:add 20 1) ⇝ false");
+    assert_eq!(output[2], "λ _: Nat, Nat:even _0 ⇝ λ _: Nat, Nat:even _0");
+    assert_eq!(
+        output[4],
+        "even depends on Eq, Eq:refl, Bool, true, Nat (native), Nat:even (native)"
+    );
+    assert!(kernel.assumptions("Nat:add 1 true").is_err());
+    assert_eq!(
+        kernel.assumptions("Nat:succ 1").unwrap(),
+        ["Nat (native)", "Nat:succ (native)"]
+    );
+
+    // Removing a constant the extension mentions drops the extension, but not its axiom.
+    kernel.add("remove true").unwrap();
+    let output = kernel.add("reduce Nat:even 2;").unwrap();
+    assert_eq!(output[0], "Nat:even 2 ⇝ Nat:even 2");
+    assert!(kernel.extend(Arc::new(Even)).is_err());
+
+    // A result of the wrong type is not used.
+    struct Wrong;
+    impl Extension for Wrong {
+        fn name(&self) -> &str {
+            "Nat:wrong"
+        }
+        fn r#type(&self) -> &str {
+            "∀ n: Nat, Nat"
+        }
+        fn arity(&self) -> usize {
+            1
+        }
+        fn constants(&self) -> &[&str] {
+            &["false"]
+        }
+        fn reduce(&self, _: &[Term]) -> Option<Term> {
+            Some(Term::Const(0))
+        }
+    }
+    let mut kernel = crate::Kernel::new();
+    kernel.extend(Arc::new(Wrong)).unwrap();
+    let output = kernel.add("reduce Nat:wrong 2;").unwrap();
+    assert_eq!(output[0], "Nat:wrong 2 ⇝ Nat:wrong 2");
+}
+
+#[test]
+fn sorry() {
+    let mut kernel = crate::Kernel::new();
+    // `sorry` is only declared once the input mentions it, and not in a comment.
+    kernel
+        .add("def sorry_free: Bool := true; // sorry")
+        .unwrap();
+    assert!(kernel.0.lookup("sorry").is_none());
+    // It is kept only once an accepted declaration uses it as a constant.
+    kernel
+        .add("def bound: ∀ sorry: Bool, Bool := λ sorry: Bool, sorry;")
+        .unwrap();
+    kernel
+        .add("def rejected: Bool := sorry Level:0;")
+        .unwrap_err();
+    let output = kernel.add("check sorry (Level:s Level:0) Bool;").unwrap();
+    assert_eq!(output, ["sorry (Level:s Level:0) Bool : Bool (uses sorry)"]);
+    assert!(kernel.0.lookup("sorry").is_none());
+    let err = kernel.add("def sorry: Bool := true;").unwrap_err();
+    assert!(err.contains("`sorry` is reserved"), "{err}");
+    let output = kernel
+        .add(
+            "def lemma: ∀ P: Sort Level:0, P := sorry;
+            def uses: Eq (Level:s Level:0) Bool true false := lemma (Eq (Level:s Level:0) Bool true false);
+            def clean: Bool := true;
+            def explicit: Bool := sorry (Level:s Level:0) Bool;
+            def nested: Bool := Bool:elim (Level:s Level:0) (λ t: Bool, Bool) true false clean, with {
+                def inner: Bool := sorry;
+            }
+            check uses;
+            check clean;
+            defeq clean, explicit;
+            assumptions uses;",
+        )
+        .unwrap();
+    assert_eq!(
+        output,
+        [
+            "uses : Eq (Level:s Level:0) Bool true false (uses sorry)",
+            "clean : Bool",
+            "clean ≢ explicit (uses sorry)",
+            "uses depends on Eq, Bool, false, true, sorry (uses sorry)",
+        ]
+    );
+    assert!(kernel.sorries().eq(["lemma", "uses", "explicit", "inner"]));
+
+    let err = kernel
+        .add("def bad: Sort Level:0 := sorry Level:0;")
+        .unwrap_err();
+    assert!(err.contains("type mismatch"), "{err}");
+    let err = kernel.add("def large: Sortω₀ := sorry;").unwrap_err();
+    assert!(err.contains("not in a `Sort`"), "{err}");
+
+    // Removing `sorry` removes everything that depends on it.
+    kernel.add("remove sorry").unwrap();
+    assert_eq!(kernel.sorries().count(), 0);
+    kernel.add("def again: Bool := sorry;").unwrap();
+    assert!(kernel.sorries().eq(["again"]));
+
+    // In a batch, a bare `sorry` needs the declarations its type mentions to be checked.
+    let input = "def c: ∀ x: Bool Bool, Sort Level:0 := Bool;
+        def k: Bool true := true;
+        def d: c k := sorry;";
+    let err = kernel.add_parallel(input, 4).unwrap_err();
+    assert!(err.contains("`sorry` at a type that mentions `c`"), "{err}");
+    let err = kernel.add(input).unwrap_err();
+    assert!(err.contains("not Π type"), "{err}");
+    kernel.add_parallel("def e: Bool := sorry;", 4).unwrap();
+    assert!(kernel.sorries().eq(["again", "e"]));
+}
+
+#[test]
+fn quot() {
+    let mut kernel = crate::Kernel::new();
+    let r = "(Eq (Level:s Level:0) Bool)";
+    let lift = format!("Quot:lift (Level:s Level:0) (Level:s Level:0) Bool {r} Bool (λ a: Bool, a) (λ a: Bool, λ b: Bool, λ p: {r} a b, p)");
+    let mk = format!("Quot:mk (Level:s Level:0) Bool {r}");
+    let output = kernel
+        .add(&format!(
+            "def lift_mk: Eq (Level:s Level:0) Bool ({lift} ({mk} false)) false := Eq:refl (Level:s Level:0) Bool false;
+            def sound: Eq (Level:s Level:0) (Quot (Level:s Level:0) Bool {r}) ({mk} true) ({mk} true) :=
+                Quot:sound (Level:s Level:0) Bool {r} true true (Eq:refl (Level:s Level:0) Bool true);
+            reduce Quot:lift (Level:s Level:0) (Level:s Level:0) Bool {r} (∀ b: Bool, Bool)
+                (λ a: Bool, λ b: Bool, a) (λ a: Bool, λ b: Bool, λ p: {r} a b,
+                    Eq:elim (Level:s Level:0) Level:0 Bool (λ c: Bool, Eq (Level:s Level:0) (∀ b: Bool, Bool)
+                        (λ b: Bool, a) (λ b: Bool, c)) a (Eq:refl (Level:s Level:0) (∀ b: Bool, Bool) (λ b: Bool, a)) b p)
+                ({mk} true) false;
+            defeq {lift} ({mk} true), {lift} ({mk} false);"
+        ))
+        .unwrap();
+    assert!(output[0].ends_with(" ⇝ true"), "{}", output[0]);
+    assert!(output[2].contains(" ≢ "), "{}", output[2]);
+
+    let certificate = kernel
+        .certify(&format!(
+            "(λ h: Eq (Level:s Level:0) Bool ({lift} ({mk} false)) false, h) (Eq:refl (Level:s Level:0) Bool false)"
+        ))
+        .unwrap();
+    assert!(kernel.check_certificate(&certificate).is_ok());
+
+    // The computation rule only applies to `Quot:mk`.
+    let e = kernel
+        .add(&format!(
+            "def stuck: ∀ q: Quot (Level:s Level:0) Bool {r}, Eq (Level:s Level:0) Bool ({lift} q) true :=
+                λ q: Quot (Level:s Level:0) Bool {r}, Eq:refl (Level:s Level:0) Bool true;"
+        ))
+        .unwrap_err();
+    assert!(e.starts_with("type mismatch"), "{e}");
+
+    assert!(kernel.export_dedukti().unwrap().contains(
+        "\n[u, v, A, r, B, f, h, a] {|Quot:lift|} u v A r B f h ({|Quot:mk|} _ _ _ a) --> f a.\n"
+    ));
+    assert_eq!(
+        crate::Kernel::new().import_lean("#QUOT").unwrap(),
+        [
+            "mapped `Quot` to `Quot`",
+            "mapped `Quot.mk` to `Quot:mk`",
+            "mapped `Quot.lift` to `Quot:lift`",
+            "mapped `Quot.ind` to `Quot:ind`",
+            "imported 0 declarations, skipped 0",
+        ]
+    );
+}
+
+#[test]
+fn sigma_eta() {
+    let mut kernel = crate::Kernel::new();
+    let params = "(Level:s Level:0) Bool (λ a: Bool, Bool)";
+    let sigma = format!("Sigma {params}");
+    let eta = format!("Sigma:mk {params} (Sigma:fst {params} t) (Sigma:snd {params} t)");
+    let output = kernel
+        .add(&format!(
+            "reduce Sigma:fst {params} (Sigma:mk {params} true false);
+            reduce Sigma:snd {params} (Sigma:mk {params} true false);
+            def eta: ∀ t: {sigma}, Eq (Level:s Level:0) ({sigma}) t ({eta}) :=
+                λ t: {sigma}, Eq:refl (Level:s Level:0) ({sigma}) t;
+            def snd_mk: Eq (Level:s Level:0) Bool (Sigma:snd {params} (Sigma:mk {params} true false)) false :=
+                Eq:refl (Level:s Level:0) Bool false;"
+        ))
+        .unwrap();
+    assert!(output[0].ends_with(" ⇝ true"), "{}", output[0]);
+    assert!(output[2].ends_with(" ⇝ false"), "{}", output[2]);
+
+    // The components must be the projections, in order.
+    let swapped = format!("Sigma:mk {params} (Sigma:snd {params} t) (Sigma:fst {params} t)");
+    kernel
+        .add(&format!(
+            "def swapped: ∀ t: {sigma}, Eq (Level:s Level:0) ({sigma}) t ({swapped}) :=
+                λ t: {sigma}, Eq:refl (Level:s Level:0) ({sigma}) t;"
+        ))
+        .unwrap_err();
+
+    let certificate = kernel
+        .certify(&format!(
+            "λ t: {sigma}, (λ h: Eq (Level:s Level:0) ({sigma}) ({eta}) t, h) (Eq:refl (Level:s Level:0) ({sigma}) t)"
+        ))
+        .unwrap();
+    assert!(certificate.contains("(defeq eta "));
+    assert!(kernel.check_certificate(&certificate).is_ok());
+    assert!(kernel
+        .check_certificate(&certificate.replace("(defeq eta ", "(defeq level "))
+        .is_err());
+
+    assert!(kernel
+        .export_dedukti()
+        .unwrap()
+        .contains("\n[u, A, B, a, b] {|Sigma:snd|} u A B ({|Sigma:mk|} _ _ _ a b) --> b.\n"));
+    assert!(kernel
+        .zfc(&format!(
+            "Sigma:fst {params} (Sigma:mk {params} true false)"
+        ))
+        .is_ok());
+}
+
+#[test]
+fn names() {
+    use crate::name::Name;
+    use crate::name::Names;
+
+    let mut names = Names::new();
+    let mk = names.intern("Sigma:mk");
+    assert_eq!(names.intern("Sigma:mk"), mk);
+    assert_eq!(names.get("Sigma:mk"), Some(mk));
+    assert_eq!(names.get("Sigma:fst"), None);
+    let sigma = names.get("Sigma").unwrap();
+    assert_eq!(names.members(Name::ROOT).collect::<Vec<_>>(), [sigma]);
+    assert_eq!(names.member(sigma, "mk"), Some(mk));
+    for text in ["Sigma:mk", "Sigma", "a::b:", ":", ""] {
+        let name = names.intern(text);
+        assert_eq!(&names[name], text);
+        assert_eq!(names.get(text), Some(name));
+    }
+    assert_ne!(names.get(""), Some(Name::ROOT));
+
+    let mut kernel = crate::Kernel::new();
+    let sigma: Vec<_> = kernel.namespace("Sigma").collect();
+    assert_eq!(
+        sigma,
+        [
+            "Sigma:mk",
+            "Sigma:elim",
+            "Sigma:mk_elim",
+            "Sigma:fst",
+            "Sigma:snd"
+        ]
+    );
+    let level: Vec<_> = kernel.namespace("Level").collect();
+    assert_eq!(level, ["Level:0", "Level:s", "Level:max", "Level:imax"]);
+    let top: Vec<_> = kernel.namespace("").collect();
+    assert!(top.starts_with(&["Level", "Sort", "Eq", "funext"]));
+    assert!(!top.contains(&"Eq:refl"));
+
+    kernel
+        .add("def Foo:bar:baz: Bool := true; def Foo:bar:qux: Bool := Foo:bar:baz;")
+        .unwrap();
+    assert_eq!(kernel.namespace("Foo").count(), 0);
+    let bar: Vec<_> = kernel.namespace("Foo:bar").collect();
+    assert_eq!(bar, ["Foo:bar:baz", "Foo:bar:qux"]);
+    assert_eq!(kernel.namespace("Foo:baz").count(), 0);
+    kernel.add("remove Foo:bar:qux").unwrap();
+    let bar: Vec<_> = kernel.namespace("Foo:bar").collect();
+    assert_eq!(bar, ["Foo:bar:baz"]);
+    let snapshot = kernel.snapshot();
+    kernel.add("def Foo:bar:quux: Bool := false;").unwrap();
+    assert_eq!(kernel.namespace("Foo:bar").count(), 2);
+    kernel.restore(&snapshot);
+    assert_eq!(kernel.namespace("Foo:bar").count(), 1);
+}
+
+#[test]
+fn generated() {
+    use crate::generate::Generator;
+
+    let kernel = crate::Kernel::new();
+    let (parse, st) = (&kernel.0, kernel.0.kernel());
+    for seed in 0..200 {
+        let mut generator = Generator::new(seed);
+        let (r#type, term, rewritten) = generator.sample();
+        let check = |s: &str| {
+            parse
+                .check_expr(s)
+                .unwrap_or_else(|e| panic!("seed {seed}: `{s}` is ill-typed: {e}"))
+        };
+        let (e, e_type) = check(&term);
+        let (r, r_type) = check(&rewritten);
+        let (expected, _) = check(&r#type.to_string());
+        assert!(st.def_eq(&e_type, &expected), "seed {seed}: {term}");
+        assert!(st.def_eq(&r_type, &expected), "seed {seed}: {rewritten}");
+
+        for reduced in [st.whnf(&e), st.normalize(&e)] {
+            let reduced_type = st.type_of(&reduced).unwrap();
+            assert!(st.def_eq(&reduced_type, &e_type), "seed {seed}: {term}");
+        }
+        assert!(st.def_eq(&e, &e), "seed {seed}: {term}");
+        assert!(st.def_eq(&e, &r), "seed {seed}: {term} ≢ {rewritten}");
+        assert!(st.def_eq(&r, &e), "seed {seed}: {rewritten} ≢ {term}");
+
+        let other = generator.term(&r#type, 3, &mut Vec::new());
+        let (o, _) = check(&other);
+        assert_eq!(
+            st.def_eq(&e, &o),
+            st.def_eq(&o, &e),
+            "seed {seed}: {term}, {other}"
+        );
+    }
+}
+
 fn typecheck(s: &str) -> Result<(), String> {
     crate::parse::State::new().check_expr(s).map(drop)
 }
@@ -0,0 +1,544 @@
+//! The translation of checked terms into ZFC, following section 3 of `main.typ`.
+//!
+//! A term whose type lives in some `Sort l` translates to a set:
+//! Π types to products, abstractions to set functions and applications to function values.
+//! Terms whose type lives in `Sortω` translate to functions on translations instead,
+//! like the large terms of Sett.
+//! `Sort l` translates to the universe `𝒰(l)`, where `𝒰(0) = 𝒫({∅})`
+//! and `𝒰(n + 1)` is the `n`-th Grothendieck universe, so levels translate to natural numbers.
+//! Proofs translate to `∅`, which plays the role of `•`.
+//! The W types are Sett's `Tree`, bounded by a universe instead of `V_λ`.
+//! `Nat` translates to `ω`, and its literals to finite ordinals, written out in full.
+
+/// A term of set theory, with variables numbered uniquely across a translation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Term {
+    Var(u32),
+    Empty,
+    Omega,
+    Singleton(Box<Term>),
+    Union(Box<Term>, Box<Term>),
+    Pair(Box<Term>, Box<Term>),
+    Fst(Box<Term>),
+    Snd(Box<Term>),
+    /// The universe of the given level.
+    Universe(Box<Term>),
+    App(Box<Term>, Box<Term>),
+    /// The function on the given domain.
+    Lam(u32, Box<Term>, Box<Term>),
+    Product(u32, Box<Term>, Box<Term>),
+    Sum(u32, Box<Term>, Box<Term>),
+    Sep(u32, Box<Term>, Box<Formula>),
+    Iota(u32, Box<Formula>),
+    If(Box<Formula>, Box<Term>, Box<Term>),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Formula {
+    Eq(Term, Term),
+    In(Term, Term),
+    Subset(Term, Term),
+    And(Box<Formula>, Box<Formula>),
+    Implies(Box<Formula>, Box<Formula>),
+    Forall(u32, Box<Formula>),
+    Exists(u32, Box<Formula>),
+}
+
+/// A translation: a set, or a function on translations, shown applied to a fresh variable.
+pub(crate) enum Translation {
+    Set(Term),
+    Fun(u32, Box<Translation>),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Syntax {
+    Plain,
+    Metamath,
+}
+
+pub(crate) fn translate(kernel: &kernel::State, e: &Expr) -> Result<Translation, String> {
+    let translator = Translator {
+        kernel,
+        fresh: Cell::new(0),
+        defs: RefCell::new(BTreeMap::new()),
+    };
+    let translation = translator.expr(&[], &[], e)?.reify(&translator);
+    translation
+}
+
+impl Translation {
+    /// Writes the translation in the given syntax.
+    /// `set.mm` has no functions on classes, so only sets can be written as Metamath.
+    pub(crate) fn show(&self, syntax: Syntax) -> Result<String, String> {
+        match (self, syntax) {
+            (Self::Set(t), _) => Ok(Show(t, syntax).to_string()),
+            (Self::Fun(x, body), Syntax::Plain) => Ok(format!("λ x{x}. {}", body.show(syntax)?)),
+            (Self::Fun(..), Syntax::Metamath) => Err(
+                "large terms translate to functions on sets, which `set.mm` cannot express"
+                    .to_owned(),
+            ),
+        }
+    }
+}
+
+#[derive(Clone)]
+enum Value<'t> {
+    Set(Term),
+    Fun(Rc<dyn Fn(Value<'t>) -> Result<Value<'t>, String> + 't>),
+}
+
+impl<'t> Value<'t> {
+    fn reify(self, t: &'t Translator<'_>) -> Result<Translation, String> {
+        match self {
+            Value::Set(term) => Ok(Translation::Set(term)),
+            Value::Fun(f) => {
+                let x = t.fresh();
+                Ok(Translation::Fun(x, Box::new(f(var(x))?.reify(t)?)))
+            }
+        }
+    }
+    fn set(self) -> Result<Term, String> {
+        match self {
+            Value::Set(term) => Ok(term),
+            Value::Fun(_) => Err("expected a set, found a function on sets".to_owned()),
+        }
+    }
+    fn apply(self, arg: Value<'t>) -> Result<Value<'t>, String> {
+        match self {
+            Value::Set(f) => Ok(Value::Set(Term::App(f.into(), arg.set()?.into()))),
+            Value::Fun(f) => f(arg),
+        }
+    }
+}
+
+struct Translator<'k> {
+    kernel: &'k kernel::State,
+    fresh: Cell<u32>,
+    /// The translations of the definitions unfolded so far.
+    defs: RefCell<BTreeMap<u32, Term>>,
+}
+
+impl<'k> Translator<'k> {
+    fn fresh(&self) -> u32 {
+        let x = self.fresh.get();
+        self.fresh.set(x + 1);
+        x
+    }
+    /// Translates `e` under binders of types `bvars`, whose translations are `values`.
+    fn expr<'t>(
+        &'t self,
+        values: &[Value<'t>],
+        bvars: &[Expr],
+        e: &Expr,
+    ) -> Result<Value<'t>, String> {
+        match e {
+            &Expr::BVar(n) => Ok(values[values.len() - 1 - usize::from(n)].clone()),
+            &Expr::FVar(n) => self.constant(n, &[]),
+            Expr::Sortω(_) => Err("`Sortω` is not a set".to_owned()),
+            Expr::Nat(n) => match n.to_u64().filter(|&n| n <= MAX_LITERAL) {
+                Some(n) => Ok(Value::Set((0..n).fold(Term::Empty, |n, _| succ(n)))),
+                None => Err(format!("the literal {n} is too large to write out")),
+            },
+            Expr::Pi(l, r) => {
+                if !self.small(bvars, e)? {
+                    return Err(format!("`{}` is not a set", self.kernel.display(e)));
+                }
+                let (x, domain) = (self.fresh(), self.expr(values, bvars, l)?.set()?);
+                let codomain = self.under(values, bvars, l, var(x), r)?.set()?;
+                Ok(Value::Set(Term::Product(x, domain.into(), codomain.into())))
+            }
+            Expr::Lam(l, r) => {
+                let r#type = self.kernel.type_in(bvars, e)?;
+                if self.small(bvars, &r#type)? {
+                    let (x, domain) = (self.fresh(), self.expr(values, bvars, l)?.set()?);
+                    let body = self.under(values, bvars, l, var(x), r)?.set()?;
+                    return Ok(Value::Set(Term::Lam(x, domain.into(), body.into())));
+                }
+                let (values, bvars, l, r) = (values.to_vec(), bvars.to_vec(), l.clone(), r.clone());
+                Ok(Value::Fun(Rc::new(move |v| {
+                    self.under(&values, &bvars, &l, v, &r)
+                })))
+            }
+            Expr::App(..) => {
+                let (mut head, mut args) = (e, Vec::new());
+                while let Expr::App(f, arg) = head {
+                    args.push(&**arg);
+                    head = f;
+                }
+                args.reverse();
+                let args = args.iter().map(|a| self.expr(values, bvars, a));
+                let args = args.collect::<Result<Vec<_>, _>>()?;
+                let (f, rest) = match *head {
+                    Expr::FVar(n) => {
+                        let arity = arity(self.kernel.name_of(n)).unwrap_or(0).min(args.len());
+                        (self.constant(n, &args[..arity])?, &args[arity..])
+                    }
+                    _ => (self.expr(values, bvars, head)?, &args[..]),
+                };
+                rest.iter().try_fold(f, |f, a| f.apply(a.clone()))
+            }
+        }
+    }
+    fn under<'t>(
+        &'t self,
+        values: &[Value<'t>],
+        bvars: &[Expr],
+        r#type: &Expr,
+        v: Value<'t>,
+        body: &Expr,
+    ) -> Result<Value<'t>, String> {
+        let values = [values, &[v]].concat();
+        let bvars = [bvars, slice::from_ref(r#type)].concat();
+        self.expr(&values, &bvars, body)
+    }
+    /// Whether the type `r#type` lives in some `Sort l`, rather than in `Sortω`.
+    fn small(&self, bvars: &[Expr], r#type: &Expr) -> Result<bool, String> {
+        match self.kernel.type_in(bvars, r#type)? {
+            Expr::App(l, _) if *l == SORT => Ok(true),
+            Expr::Sortω(_) => Ok(false),
+            univ => Err(format!("`{}` is not a sort", self.kernel.display(&univ))),
+        }
+    }
+    /// Translates the constant `n` applied to its first `args`.
+    fn constant<'t>(&'t self, n: u32, args: &[Value<'t>]) -> Result<Value<'t>, String> {
+        let (def, name) = (self.kernel.def(n), self.kernel.name_of(n));
+        if let Some(arity) = arity(name) {
+            let (mut bvars, mut r#type) = (Vec::new(), &def.r#type);
+            for _ in args {
+                let Expr::Pi(l, r) = r#type else {
+                    unreachable!()
+                };
+                bvars.push((**l).clone());
+                r#type = r;
+            }
+            return self.curry(name, arity, args.to_vec(), bvars, r#type.clone());
+        }
+        let Some(value) = &def.value else {
+            return Err(format!("`{name}` has no translation"));
+        };
+        if let Some(term) = self.defs.borrow().get(&n) {
+            return Ok(Value::Set(term.clone()));
+        }
+        let v = self.expr(&[], &[], value)?;
+        if let Value::Set(term) = &v {
+            self.defs.borrow_mut().insert(n, term.clone());
+        }
+        Ok(v)
+    }
+    /// Translates the axiom `name` given the first `values` of its `arity` arguments,
+    /// where `r#type` is the rest of its type under binders of types `bvars`.
+    /// The missing arguments are abstracted over by set functions or functions on sets.
+    fn curry<'t>(
+        &'t self,
+        name: &'k str,
+        arity: usize,
+        values: Vec<Value<'t>>,
+        bvars: Vec<Expr>,
+        r#type: Expr,
+    ) -> Result<Value<'t>, String> {
+        if values.len() == arity {
+            let args = values.into_iter().map(Value::set);
+            return Ok(Value::Set(axiom(
+                self,
+                name,
+                &args.collect::<Result<Vec<_>, _>>()?,
+            )));
+        }
+        let Expr::Pi(l, r) = &r#type else {
+            unreachable!()
+        };
+        let (l, r) = ((**l).clone(), (**r).clone());
+        if self.small(&bvars, &r#type)? {
+            let (x, domain) = (self.fresh(), self.expr(&values, &bvars, &l)?.set()?);
+            let (values, bvars) = ([values, vec![var(x)]].concat(), [bvars, vec![l]].concat());
+            let body = self.curry(name, arity, values, bvars, r)?.set()?;
+            return Ok(Value::Set(Term::Lam(x, domain.into(), body.into())));
+        }
+        Ok(Value::Fun(Rc::new(move |v| {
+            let (values, bvars) = (
+                [&values[..], &[v]].concat(),
+                [&bvars[..], slice::from_ref(&l)].concat(),
+            );
+            self.curry(name, arity, values, bvars, r.clone())
+        })))
+    }
+}
+
+/// The number of arguments of a builtin or axiom that has a translation.
+fn arity(name: &str) -> Option<usize> {
+    Some(match name {
+        "Level" | "Level:0" | "Bool" | "false" | "true" | "Nat" | "Nat:zero" => 0,
+        "Level:s" | "Sort" | "Nat:succ" => 1,
+        "Level:max" | "Level:imax" | "Inhabited" => 2,
+        "Eq:refl" | "Sigma" | "ULift" | "W" | "Inhabited:mk" => 3,
+        "Eq" | "propext" | "false_elim" | "true_elim" | "Sigma:fst" | "Sigma:snd" => 4,
+        "ULift:up" | "ULift:down" | "ULift:up_down" | "ULift:down_up" => 4,
+        "Sigma:mk" | "W:mk" | "Bool:elim" => 5,
+        "Eq:refl_elim" => 6,
+        "funext" | "Sigma:elim" | "W:elim" | "Inhabited:elim_subsingleton" => 7,
+        "Eq:elim" | "Sigma:mk_elim" | "W:mk_elim" => 8,
+        _ => return None,
+    })
+}
+
+/// The translation of a builtin or axiom applied to all of its arguments,
+/// which all live in small types.
+fn axiom(t: &Translator<'_>, name: &str, args: &[Term]) -> Term {
+    let app = |f: &Term, a: Term| Term::App(f.clone().into(), a.into());
+    let pair = |a: Term, b: Term| Term::Pair(a.into(), b.into());
+    match (name, args) {
+        ("Level" | "Nat", []) => Term::Omega,
+        ("Level:0" | "false" | "Nat:zero", []) => Term::Empty,
+        ("true", []) => bullets(),
+        ("Bool", []) => union(
+            Term::Singleton(Term::Empty.into()),
+            Term::Singleton(bullets().into()),
+        ),
+        ("Level:s" | "Nat:succ", [n]) => succ(n.clone()),
+        ("Level:max", [a, b]) => union(a.clone(), b.clone()),
+        ("Level:imax", [a, b]) => {
+            let zero = Formula::Eq(b.clone(), Term::Empty);
+            Term::If(
+                zero.into(),
+                Term::Empty.into(),
+                union(a.clone(), b.clone()).into(),
+            )
+        }
+        ("Sort", [l]) => Term::Universe(l.clone().into()),
+        ("Eq", [_, _, a, b]) => {
+            let eq = Formula::Eq(a.clone(), b.clone());
+            Term::Sep(t.fresh(), bullets().into(), eq.into())
+        }
+        ("Eq:elim", [.., h, _, _]) => h.clone(),
+        ("Sigma", [_, alpha, beta]) => {
+            let a = t.fresh();
+            Term::Sum(a, alpha.clone().into(), app(beta, Term::Var(a)).into())
+        }
+        ("Sigma:mk" | "W:mk", [.., a, b]) => pair(a.clone(), b.clone()),
+        ("Sigma:fst", [.., p]) => Term::Fst(p.clone().into()),
+        ("Sigma:snd", [.., p]) => Term::Snd(p.clone().into()),
+        ("Sigma:elim", [.., h, p]) => {
+            let h = app(h, Term::Fst(p.clone().into()));
+            app(&h, Term::Snd(p.clone().into()))
+        }
+        ("Bool:elim", [_, _, h1, h2, b]) => {
+            let zero = Formula::Eq(b.clone(), Term::Empty);
+            Term::If(zero.into(), h1.clone().into(), h2.clone().into())
+        }
+        ("ULift" | "ULift:up" | "ULift:down", [.., a]) => a.clone(),
+        ("W", [u, alpha, beta]) => w(t, u, alpha, beta),
+        ("W:elim", [u, _, alpha, beta, motive, f, tree]) => {
+            let w = w(t, u, alpha, beta);
+            let (x, set, s, a, b, h) = (
+                t.fresh(),
+                t.fresh(),
+                t.fresh(),
+                t.fresh(),
+                t.fresh(),
+                t.fresh(),
+            );
+            let (var, set_var) = (|x| Term::Var(x), Term::Var(set));
+            let beta_a = app(beta, var(a));
+            let sum = Term::Sum(s, w.clone().into(), app(motive, var(s)).into());
+            let i = t.fresh();
+            let branches = Term::Product(i, beta_a.clone().into(), w.into());
+            let i = t.fresh();
+            let results = app(motive, app(&var(b), var(i)));
+            let results = Term::Product(i, beta_a.clone().into(), results.into());
+            let i = t.fresh();
+            let below = pair(app(&var(b), var(i)), app(&var(h), var(i)));
+            let below = implies(
+                Formula::In(var(i), beta_a),
+                Formula::In(below, set_var.clone()),
+            );
+            let hypotheses = [
+                Formula::In(var(a), alpha.clone()),
+                Formula::In(var(b), branches),
+                Formula::In(var(h), results),
+                Formula::Forall(i, below.into()),
+            ];
+            let hypotheses = hypotheses.into_iter().reduce(and).unwrap();
+            let result = app(&app(&app(f, var(a)), var(b)), var(h));
+            let step = Formula::In(pair(pair(var(a), var(b)), result), set_var.clone());
+            let step = [a, b, h]
+                .into_iter()
+                .rev()
+                .fold(implies(hypotheses, step), |φ, x| {
+                    Formula::Forall(x, φ.into())
+                });
+            let member = Formula::In(pair(tree.clone(), var(x)), set_var.clone());
+            let φ = implies(Formula::Subset(set_var, sum), implies(step, member));
+            Term::Iota(x, Formula::Forall(set, φ.into()).into())
+        }
+        ("Inhabited", [_, alpha]) => {
+            let y = t.fresh();
+            let inhabited = Formula::Exists(y, Formula::In(Term::Var(y), alpha.clone()).into());
+            Term::Sep(t.fresh(), bullets().into(), inhabited.into())
+        }
+        ("Inhabited:elim_subsingleton", [_, _, alpha, _, _, f, _]) => {
+            let (x, a) = (t.fresh(), t.fresh());
+            let member = Formula::In(Term::Var(a), alpha.clone());
+            let value = Formula::Eq(Term::Var(x), app(f, Term::Var(a)));
+            Term::Iota(x, Formula::Exists(a, and(member, value).into()).into())
+        }
+        // Proofs.
+        _ => Term::Empty,
+    }
+}
+
+/// Sett's `Tree`: the least set closed under pairing an `a ∈ alpha` with a function from `beta(a)`,
+/// within the universe `𝒰(max(u, 1))` rather than some `V_λ`.
+fn w(t: &Translator<'_>, u: &Term, alpha: &Term, beta: &Term) -> Term {
+    let (x, set, a, b, i) = (t.fresh(), t.fresh(), t.fresh(), t.fresh(), t.fresh());
+    let beta_a = Term::App(beta.clone().into(), Term::Var(a).into());
+    let branches = Term::Product(i, beta_a.into(), Term::Var(set).into());
+    let hypotheses = and(
+        Formula::In(Term::Var(a), alpha.clone()),
+        Formula::In(Term::Var(b), branches),
+    );
+    let pair = Term::Pair(Term::Var(a).into(), Term::Var(b).into());
+    let closed = implies(hypotheses, Formula::In(pair, Term::Var(set)));
+    let closed = Formula::Forall(a, Formula::Forall(b, closed.into()).into());
+    let least = implies(closed, Formula::In(Term::Var(x), Term::Var(set)));
+    let universe = Term::Universe(union(u.clone(), bullets()).into());
+    Term::Sep(
+        x,
+        universe.into(),
+        Formula::Forall(set, least.into()).into(),
+    )
+}
+
+/// `{•}`, which is also `1` and `true`.
+fn bullets() -> Term {
+    Term::Singleton(Term::Empty.into())
+}
+
+/// The largest literal that is translated, since ordinals double in size with each successor.
+const MAX_LITERAL: u64 = 16;
+
+fn succ(n: Term) -> Term {
+    union(n.clone(), Term::Singleton(n.into()))
+}
+
+fn union(a: Term, b: Term) -> Term {
+    Term::Union(a.into(), b.into())
+}
+
+fn and(φ: Formula, ψ: Formula) -> Formula {
+    Formula::And(φ.into(), ψ.into())
+}
+
+fn implies(φ: Formula, ψ: Formula) -> Formula {
+    Formula::Implies(φ.into(), ψ.into())
+}
+
+struct Show<'a>(&'a Term, Syntax);
+
+impl Display for Show<'_> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let (term, syntax) = (self.0, self.1);
+        let show = |t| Show(t, syntax);
+        let plain = syntax == Syntax::Plain;
+        match term {
+            Term::Var(x) => write!(f, "x{x}"),
+            Term::Empty => f.write_str(if plain { "∅" } else { "(/)" }),
+            Term::Omega => f.write_str(if plain { "ω" } else { "om" }),
+            Term::Singleton(a) if plain => write!(f, "{{{}}}", show(a)),
+            Term::Singleton(a) => write!(f, "{{ {} }}", show(a)),
+            Term::Union(a, b) if plain => write!(f, "({} ∪ {})", show(a), show(b)),
+            Term::Union(a, b) => write!(f, "( {} u. {} )", show(a), show(b)),
+            Term::Pair(a, b) if plain => write!(f, "⟨{}, {}⟩", show(a), show(b)),
+            Term::Pair(a, b) => write!(f, "<. {} , {} >.", show(a), show(b)),
+            Term::Fst(a) if plain => write!(f, "π₁({})", show(a)),
+            Term::Fst(a) => write!(f, "( 1st ` {} )", show(a)),
+            Term::Snd(a) if plain => write!(f, "π₂({})", show(a)),
+            Term::Snd(a) => write!(f, "( 2nd ` {} )", show(a)),
+            Term::Universe(l) if plain => write!(f, "𝒰({})", show(l)),
+            Term::Universe(l) => write!(f, "( U ` {} )", show(l)),
+            Term::App(g, a) if plain => write!(f, "{}({})", show(g), show(a)),
+            Term::App(g, a) => write!(f, "( {} ` {} )", show(g), show(a)),
+            Term::Lam(x, a, b) if plain => write!(f, "(x{x} ∈ {} ↦ {})", show(a), show(b)),
+            Term::Lam(x, a, b) => write!(f, "( x{x} e. {} |-> {} )", show(a), show(b)),
+            Term::Product(x, a, b) if plain => write!(f, "(∏ x{x} ∈ {}. {})", show(a), show(b)),
+            Term::Product(x, a, b) => write!(f, "X_ x{x} e. {} {}", show(a), show(b)),
+            Term::Sum(x, a, b) if plain => write!(f, "(∑ x{x} ∈ {}. {})", show(a), show(b)),
+            Term::Sum(x, a, b) => {
+                write!(f, "U_ x{x} e. {} ( {{ x{x} }} X. {} )", show(a), show(b))
+            }
+            Term::Sep(x, a, φ) if plain => {
+                write!(f, "{{x{x} ∈ {} | {}}}", show(a), ShowFormula(φ, syntax))
+            }
+            Term::Sep(x, a, φ) => {
+                write!(f, "{{ x{x} e. {} | {} }}", show(a), ShowFormula(φ, syntax))
+            }
+            Term::Iota(x, φ) if plain => write!(f, "(ι x{x}. {})", ShowFormula(φ, syntax)),
+            Term::Iota(x, φ) => write!(f, "( iota x{x} {} )", ShowFormula(φ, syntax)),
+            Term::If(φ, a, b) if plain => {
+                write!(
+                    f,
+                    "if({}, {}, {})",
+                    ShowFormula(φ, syntax),
+                    show(a),
+                    show(b)
+                )
+            }
+            Term::If(φ, a, b) => {
+                write!(
+                    f,
+                    "if ( {} , {} , {} )",
+                    ShowFormula(φ, syntax),
+                    show(a),
+                    show(b)
+                )
+            }
+        }
+    }
+}
+
+struct ShowFormula<'a>(&'a Formula, Syntax);
+
+impl Display for ShowFormula<'_> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let (formula, syntax) = (self.0, self.1);
+        let (term, show) = (|t| Show(t, syntax), |φ| ShowFormula(φ, syntax));
+        let plain = syntax == Syntax::Plain;
+        match formula {
+            Formula::Eq(a, b) => write!(f, "{} = {}", term(a), term(b)),
+            Formula::In(a, b) if plain => write!(f, "{} ∈ {}", term(a), term(b)),
+            Formula::In(a, b) => write!(f, "{} e. {}", term(a), term(b)),
+            Formula::Subset(a, b) if plain => write!(f, "{} ⊆ {}", term(a), term(b)),
+            Formula::Subset(a, b) => write!(f, "{} C_ {}", term(a), term(b)),
+            Formula::And(φ, ψ) if plain => write!(f, "({} ∧ {})", show(φ), show(ψ)),
+            Formula::And(φ, ψ) => write!(f, "( {} /\\ {} )", show(φ), show(ψ)),
+            Formula::Implies(φ, ψ) if plain => write!(f, "({} → {})", show(φ), show(ψ)),
+            Formula::Implies(φ, ψ) => write!(f, "( {} -> {} )", show(φ), show(ψ)),
+            Formula::Forall(x, φ) if plain => write!(f, "∀x{x}. {}", show(φ)),
+            Formula::Forall(x, φ) => write!(f, "A. x{x} {}", show(φ)),
+            Formula::Exists(x, φ) if plain => write!(f, "∃x{x}. {}", show(φ)),
+            Formula::Exists(x, φ) => write!(f, "E. x{x} {}", show(φ)),
+        }
+    }
+}
+
+fn var<'t>(x: u32) -> Value<'t> {
+    Value::Set(Term::Var(x))
+}
+
+use crate::expr::Expr;
+use crate::kernel;
+use crate::kernel::builtins::*;
+use alloc::borrow::ToOwned;
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::rc::Rc;
+use alloc::string::String;
+use alloc::string::ToString;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cell::Cell;
+use core::cell::RefCell;
+use core::fmt;
+use core::fmt::Display;
+use core::fmt::Formatter;
+use core::slice;
@@ -0,0 +1,314 @@
+//! Type inference, weak head normalization and definitional equality.
+//!
+//! Every judgment is derived from scratch: nothing is cached,
+//! and terms are re-checked wherever a rule needs their type.
+
+/// The variables in scope, with their types, innermost last.
+pub(crate) type Context = Vec<(Rc<str>, Term)>;
+
+/// The type of a type.
+enum Universe {
+    Sort(Term),
+    Sortω(u16),
+}
+
+/// A level, with the terms that are not built from the level constructors as atoms.
+enum Level {
+    Zero,
+    Succ(Box<Level>),
+    Max(Box<Level>, Box<Level>),
+    IMax(Box<Level>, Box<Level>),
+    Atom(usize),
+}
+
+impl Level {
+    fn eval(&self, atoms: &[u64]) -> u64 {
+        match self {
+            Self::Zero => 0,
+            Self::Succ(l) => l.eval(atoms) + 1,
+            Self::Max(a, b) => Ord::max(a.eval(atoms), b.eval(atoms)),
+            Self::IMax(a, b) => match b.eval(atoms) {
+                0 => 0,
+                b => Ord::max(a.eval(atoms), b),
+            },
+            &Self::Atom(i) => atoms[i],
+        }
+    }
+    fn succs(&self) -> u64 {
+        match self {
+            Self::Zero | Self::Atom(_) => 0,
+            Self::Succ(l) => l.succs() + 1,
+            Self::Max(a, b) | Self::IMax(a, b) => a.succs() + b.succs(),
+        }
+    }
+}
+
+impl Checker {
+    pub(crate) fn infer(&self, cx: &mut Context, t: &Term) -> Result<Term, String> {
+        Ok(match t {
+            Term::Const(c) => self.decls[c].clone(),
+            Term::Var(x) => cx.iter().rev().find(|(y, _)| y == x).unwrap().1.clone(),
+            Term::Sortω(n) => Term::Sortω(n.checked_add(1).ok_or("Sortω overflow")?),
+            Term::Nat(_) if self.recognized("Nat") => Term::constant("Nat"),
+            Term::Nat(_) => return Err("natural number literal without `Nat`".to_owned()),
+            Term::Lam(x, a, b) => {
+                self.universe(cx, a)?;
+                let (z, b) = self.open(x, b);
+                let b_type = self.under(cx, &z, a, |cx| self.infer(cx, &b))?;
+                Term::Pi(z, a.clone(), Box::new(b_type))
+            }
+            Term::Pi(x, a, b) => {
+                let l = self.universe(cx, a)?;
+                let (z, b) = self.open(x, b);
+                let r = self.under(cx, &z, a, |cx| self.universe(cx, &b))?;
+                match (l, r) {
+                    (Universe::Sort(_), Universe::Sort(r)) if r.occurs(&z) => Term::Sortω(0),
+                    (Universe::Sort(l), Universe::Sort(r)) => {
+                        Term::constant("Sort").app([Term::constant("Level:imax").app([l, r])])
+                    }
+                    (Universe::Sortω(n), Universe::Sort(_))
+                    | (Universe::Sort(_), Universe::Sortω(n)) => Term::Sortω(n),
+                    (Universe::Sortω(n), Universe::Sortω(m)) => Term::Sortω(Ord::max(n, m)),
+                }
+            }
+            Term::App(f, a) => {
+                let Term::Pi(x, domain, codomain) = self.whnf(&self.infer(cx, f)?) else {
+                    return Err(format!("`{f}` is applied but is not a function"));
+                };
+                let a_type = self.infer(cx, a)?;
+                if !self.def_eq(cx, &domain, &a_type) {
+                    return Err(format!(
+                        "`{f}` expects `{domain}` but `{a}` is a `{a_type}`"
+                    ));
+                }
+                codomain.subst(&x, a, &self.fresh)
+            }
+        })
+    }
+    /// The universe of a type, which must literally be a `Sort` or `Sortω`.
+    fn universe(&self, cx: &mut Context, t: &Term) -> Result<Universe, String> {
+        match self.infer(cx, t)? {
+            Term::App(f, l) if matches!(&*f, Term::Const(c) if &**c == "Sort") => {
+                Ok(Universe::Sort(*l))
+            }
+            Term::Sortω(n) => Ok(Universe::Sortω(n)),
+            other => Err(format!("`{t}` is a `{other}`, which is not a sort")),
+        }
+    }
+    /// Checks that `t` is a type.
+    pub(crate) fn check_type(&self, t: &Term) -> Result<(), String> {
+        self.universe(&mut Context::new(), t).map(drop)
+    }
+    /// Renames the variable bound in `body` to a fresh one.
+    fn open(&self, x: &str, body: &Term) -> (Rc<str>, Term) {
+        let z = self.fresh.name(x);
+        let body = body.subst(x, &Term::Var(z.clone()), &self.fresh);
+        (z, body)
+    }
+    fn under<R>(
+        &self,
+        cx: &mut Context,
+        x: &Rc<str>,
+        t: &Term,
+        f: impl FnOnce(&mut Context) -> R,
+    ) -> R {
+        cx.push((x.clone(), t.clone()));
+        let r = f(cx);
+        cx.pop();
+        r
+    }
+
+    pub(crate) fn whnf(&self, t: &Term) -> Term {
+        let mut t = t.clone();
+        loop {
+            let (head, args) = t.spine();
+            t = match head {
+                Term::Lam(x, _, body) if !args.is_empty() => {
+                    let rest = args[1..].iter().map(|&a| a.clone());
+                    body.subst(x, args[0], &self.fresh)
+                        .app(rest.collect::<Vec<_>>())
+                }
+                _ => match self.reduce(&t) {
+                    Some(reduced) => reduced,
+                    None => return t,
+                },
+            };
+        }
+    }
+    /// One step of the reductions for the recognized axioms.
+    fn reduce(&self, t: &Term) -> Option<Term> {
+        let literal = |t: &Term| match self.whnf(t) {
+            Term::Nat(n) => Some(n),
+            _ => None,
+        };
+        let rest = |args: &[&Term]| args.iter().map(|&a| a.clone()).collect::<Vec<_>>();
+        if self.args(t, "Nat:zero").is_some_and(|args| args.is_empty()) {
+            return Some(Term::Nat(0));
+        }
+        if let Some(&[n]) = self.args(t, "Nat:succ").as_deref() {
+            return literal(n)?.checked_add(1).map(Term::Nat);
+        }
+        for op in OPERATIONS {
+            if let Some(&[a, b]) = self.args(t, op).as_deref() {
+                let (a, b) = (literal(a)?, literal(b)?);
+                let bool = |b: bool| {
+                    let b = if b { "true" } else { "false" };
+                    self.recognized(b).then(|| Term::constant(b))
+                };
+                return match op {
+                    "Nat:add" => a.checked_add(b).map(Term::Nat),
+                    "Nat:sub" => Some(Term::Nat(a.saturating_sub(b))),
+                    "Nat:mul" => a.checked_mul(b).map(Term::Nat),
+                    "Nat:div" => Some(Term::Nat(a.checked_div(b).unwrap_or(0))),
+                    "Nat:mod" => Some(Term::Nat(a.checked_rem(b).unwrap_or(a))),
+                    "Nat:beq" => bool(a == b),
+                    _ => bool(a <= b),
+                };
+            }
+        }
+        if let Some(args) = self.args(t, "Quot:lift").filter(|args| args.len() >= 8) {
+            let q = self.whnf(args[7]);
+            let Some(&[_, _, _, a]) = self.args(&q, "Quot:mk").as_deref() else {
+                return None;
+            };
+            return Some(args[5].clone().app([a.clone()]).app(rest(&args[8..])));
+        }
+        for (projection, field) in [("Sigma:fst", 3), ("Sigma:snd", 4)] {
+            if let Some(args) = self.args(t, projection).filter(|args| args.len() >= 4) {
+                let pair = self.whnf(args[3]);
+                let fields = self.args(&pair, "Sigma:mk").filter(|f| f.len() == 5)?;
+                return Some(fields[field].clone().app(rest(&args[4..])));
+            }
+        }
+        None
+    }
+    /// The arguments of `t` if it applies the recognized constant `name`.
+    fn args<'t>(&self, t: &'t Term, name: &str) -> Option<Vec<&'t Term>> {
+        self.recognized(name).then(|| t.args_of(name)).flatten()
+    }
+
+    pub(crate) fn def_eq(&self, cx: &mut Context, a: &Term, b: &Term) -> bool {
+        let (a, b) = (self.whnf(a), self.whnf(b));
+        self.congruent(cx, &a, &b)
+            || self.level_eq(cx, &a, &b)
+            || self.eta(cx, &a, &b)
+            || self.uip(cx, &a, &b)
+    }
+    fn congruent(&self, cx: &mut Context, a: &Term, b: &Term) -> bool {
+        match (a, b) {
+            (Term::Const(x), Term::Const(y)) | (Term::Var(x), Term::Var(y)) => x == y,
+            (Term::Sortω(n), Term::Sortω(m)) => n == m,
+            (Term::Nat(n), Term::Nat(m)) => n == m,
+            (&Term::Nat(n), t) | (t, &Term::Nat(n)) => match self.args(t, "Nat:succ").as_deref() {
+                Some(&[x]) if n != 0 => self.def_eq(cx, &Term::Nat(n - 1), x),
+                _ => false,
+            },
+            (Term::Lam(x, a, b), Term::Lam(y, c, d)) | (Term::Pi(x, a, b), Term::Pi(y, c, d)) => {
+                let z = self.fresh.name(x);
+                let (b, d) = (
+                    b.subst(x, &Term::Var(z.clone()), &self.fresh),
+                    d.subst(y, &Term::Var(z.clone()), &self.fresh),
+                );
+                self.def_eq(cx, a, c) && self.under(cx, &z, a, |cx| self.def_eq(cx, &b, &d))
+            }
+            (Term::App(f, a), Term::App(g, b)) => self.def_eq(cx, f, g) && self.def_eq(cx, a, b),
+            _ => false,
+        }
+    }
+    /// Decides the equality of two levels, one of which is built from a level constructor.
+    ///
+    /// Once each atom is known to be zero or not, a level is `max(c, x₁ + k₁, …)`
+    /// over the nonzero atoms `xᵢ` it mentions.
+    /// Two such levels agree everywhere if they agree when every nonzero atom is 1,
+    /// and when one of them is instead larger than any `c` or `kᵢ`.
+    fn level_eq(&self, cx: &mut Context, a: &Term, b: &Term) -> bool {
+        if !self.is_level(a) && !self.is_level(b) {
+            return false;
+        }
+        let mut atoms = Vec::new();
+        let l = self.level(cx, a, &mut atoms);
+        let r = self.level(cx, b, &mut atoms);
+        // The kernel gives up beyond 16 atoms.
+        if atoms.len() > 16 {
+            return false;
+        }
+        let large = l.succs() + r.succs() + 2;
+        (0..1_u32 << atoms.len()).all(|zeros| {
+            let ones = (0..atoms.len()).map(|i| u64::from(zeros >> i & 1 == 0));
+            let ones = ones.collect::<Vec<_>>();
+            let nonzero = (0..atoms.len()).filter(|&i| ones[i] == 1);
+            let points = nonzero.map(|i| {
+                let mut point = ones.clone();
+                point[i] = large;
+                point
+            });
+            let mut points = [ones.clone()].into_iter().chain(points);
+            points.all(|point| l.eval(&point) == r.eval(&point))
+        })
+    }
+    fn is_level(&self, t: &Term) -> bool {
+        matches!(
+            t.spine(),
+            (Term::Const(c), args) if matches!((&**c, args.len()),
+                ("Level:0", 0) | ("Level:s", 1) | ("Level:max" | "Level:imax", 2))
+        )
+    }
+    fn level(&self, cx: &mut Context, t: &Term, atoms: &mut Vec<Term>) -> Level {
+        let t = self.whnf(t);
+        if let (Term::Const(c), args) = t.spine() {
+            let mut level = |t: &Term| Box::new(self.level(cx, t, atoms));
+            match (&**c, &args[..]) {
+                ("Level:0", []) => return Level::Zero,
+                ("Level:s", [l]) => return Level::Succ(level(l)),
+                ("Level:max", [a, b]) => return Level::Max(level(a), level(b)),
+                ("Level:imax", [a, b]) => return Level::IMax(level(a), level(b)),
+                _ => {}
+            }
+        }
+        match atoms.iter().position(|atom| self.def_eq(cx, atom, &t)) {
+            Some(i) => Level::Atom(i),
+            None => (atoms.push(t), Level::Atom(atoms.len() - 1)).1,
+        }
+    }
+    /// The η rule for pairs.
+    fn eta(&self, cx: &mut Context, a: &Term, b: &Term) -> bool {
+        let sigma = ["Sigma", "Sigma:mk", "Sigma:fst", "Sigma:snd"];
+        if !sigma.iter().all(|name| self.recognized(name)) {
+            return false;
+        }
+        let pair = |t| self.args(t, "Sigma:mk").filter(|args| args.len() == 5);
+        let (fields, t) = match (pair(a), pair(b)) {
+            (Some(fields), None) => (fields, b),
+            (None, Some(fields)) => (fields, a),
+            _ => return false,
+        };
+        let params = fields[..3].iter().map(|&p| p.clone()).collect::<Vec<_>>();
+        let project = |name| Term::constant(name).app(params.iter().chain([t]).cloned());
+        let Ok(t_type) = self.infer(cx, t) else {
+            return false;
+        };
+        self.def_eq(cx, &t_type, &Term::constant("Sigma").app(params.clone()))
+            && self.def_eq(cx, fields[3], &project("Sigma:fst"))
+            && self.def_eq(cx, fields[4], &project("Sigma:snd"))
+    }
+    /// Proof irrelevance: any two proofs of the same proposition are equal.
+    fn uip(&self, cx: &mut Context, a: &Term, b: &Term) -> bool {
+        let Ok(a_type) = self.infer(cx, a) else {
+            return false;
+        };
+        let Ok(Universe::Sort(level)) = self.universe(cx, &a_type) else {
+            return false;
+        };
+        // Deciding this with `def_eq` would try proof irrelevance on levels again.
+        self.level_eq(cx, &self.whnf(&level), &Term::constant("Level:0"))
+            && (self.infer(cx, b)).is_ok_and(|b_type| self.def_eq(cx, &a_type, &b_type))
+    }
+}
+
+const OPERATIONS: [&str; 7] = [
+    "Nat:add", "Nat:sub", "Nat:mul", "Nat:div", "Nat:mod", "Nat:beq", "Nat:ble",
+];
+
+use crate::term::Term;
+use crate::Checker;
+use std::rc::Rc;
@@ -0,0 +1,187 @@
+//! A deliberately simple and slow checker for the kernel's type theory,
+//! to test the kernel against.
+//!
+//! It shares no code with the kernel and favours evidently correct algorithms over fast ones:
+//! variables are named, substitution renames binders only to avoid capture,
+//! nothing is cached, and level equality is decided by evaluating both sides
+//! at enough assignments of the level variables.
+//! It follows the kernel's rules, including the recognition of axioms by name and type
+//! for literals, `Quot:lift`, the projections of pairs and their η rule.
+//! It does not support comments, queries, `with` blocks or extensions,
+//! and literals are limited to `u128`; arithmetic that overflows is left unreduced.
+
+#![allow(clippy::new_without_default)]
+
+pub struct Checker {
+    decls: BTreeMap<Rc<str>, Term>,
+    /// The declared constants that are builtins or recognized axioms.
+    recognized: Vec<Rc<str>>,
+    fresh: Fresh,
+}
+
+impl Checker {
+    /// A checker with only the builtins `Level`, `Level:0`, `Level:s`, `Level:max`,
+    /// `Level:imax` and `Sort`.
+    pub fn new() -> Self {
+        let c = Term::constant;
+        let level = || c("Level");
+        let arrow = |a: Term, b: Term| Term::Pi("_".into(), Box::new(a), Box::new(b));
+        let u = Term::Var("u".into());
+        let sort_of_sort = c("Sort").app([c("Level:s").app([u])]);
+        let builtins = [
+            ("Level", c("Sort").app([c("Level:s").app([c("Level:0")])])),
+            ("Level:0", level()),
+            ("Level:s", arrow(level(), level())),
+            ("Level:max", arrow(level(), arrow(level(), level()))),
+            ("Level:imax", arrow(level(), arrow(level(), level()))),
+            (
+                "Sort",
+                Term::Pi("u".into(), Box::new(level()), Box::new(sort_of_sort)),
+            ),
+        ];
+        let recognized = builtins.iter().map(|&(name, _)| name.into()).collect();
+        let decls = builtins.into_iter().map(|(name, t)| (name.into(), t));
+        Self {
+            decls: decls.collect(),
+            recognized,
+            fresh: Fresh::default(),
+        }
+    }
+    /// Adds an axiom `name: type`.
+    pub fn axiom(&mut self, decl: &str) -> Result<(), String> {
+        let (name, r#type, rest) = self.header(decl)?;
+        self.end(rest)?;
+        self.check_type(&r#type)?;
+        self.declare(name, r#type);
+        Ok(())
+    }
+    /// Adds an opaque definition `name: type := value`.
+    pub fn define(&mut self, decl: &str) -> Result<(), String> {
+        let (name, r#type, mut rest) = self.header(decl)?;
+        term::expect(&mut rest, ":=")?;
+        let value = self.term(rest)?;
+        self.check_type(&r#type)?;
+        let value_type = self.infer(&mut Context::new(), &value)?;
+        if !self.def_eq(&mut Context::new(), &r#type, &value_type) {
+            return Err(format!(
+                "`{name}` is a `{type}` but its value is a `{value_type}`"
+            ));
+        }
+        self.declare(name, r#type);
+        Ok(())
+    }
+    /// Checks that a term is well-typed.
+    pub fn check(&self, term: &str) -> Result<(), String> {
+        self.infer(&mut Context::new(), &self.term(term)?).map(drop)
+    }
+    /// Whether two well-typed terms are definitionally equal.
+    pub fn equal(&self, lhs: &str, rhs: &str) -> Result<bool, String> {
+        let (lhs, rhs) = (self.term(lhs)?, self.term(rhs)?);
+        self.infer(&mut Context::new(), &lhs)?;
+        self.infer(&mut Context::new(), &rhs)?;
+        Ok(self.def_eq(&mut Context::new(), &lhs, &rhs))
+    }
+    fn header<'i>(&self, mut decl: &'i str) -> Result<(&'i str, Term, &'i str), String> {
+        let name = term::token(&mut decl).ok_or("unexpected end of input")?;
+        let name = name.strip_suffix(':').ok_or("no trailing colon")?;
+        if self.decls.contains_key(name) {
+            return Err(format!("duplicate declaration `{name}`"));
+        }
+        let r#type = term::parse(&mut decl, &|x| self.decls.contains_key(x))?;
+        Ok((name, r#type, decl))
+    }
+    fn term(&self, mut input: &str) -> Result<Term, String> {
+        let t = term::parse(&mut input, &|x| self.decls.contains_key(x))?;
+        self.end(input)?;
+        Ok(t)
+    }
+    fn end(&self, mut rest: &str) -> Result<(), String> {
+        match term::token(&mut rest) {
+            Some(t) => Err(format!("unexpected `{t}`")),
+            None => Ok(()),
+        }
+    }
+    /// Adds a declaration, recognizing it if it is one of [`RECOGNIZED`] with the same type.
+    fn declare(&mut self, name: &str, r#type: Term) {
+        let expected = RECOGNIZED
+            .iter()
+            .find(|&&(n, _)| n == name)
+            .and_then(|(_, t)| {
+                let t = term::parse(&mut { *t }, &|x| self.recognized(x)).ok()?;
+                t.alpha_eq(&r#type).then_some(())
+            });
+        if expected.is_some() {
+            self.recognized.push(name.into());
+        }
+        self.decls.insert(name.into(), r#type);
+    }
+    fn recognized(&self, name: &str) -> bool {
+        self.recognized.iter().any(|n| **n == *name)
+    }
+}
+
+/// The axioms with computation rules, and the types they must be declared with.
+/// Their types may only mention builtins and recognized axioms.
+const RECOGNIZED: [(&str, &str); 21] = [
+    ("Bool", "Sort (Level:s Level:0)"),
+    ("false", "Bool"),
+    ("true", "Bool"),
+    ("Nat", "Sort (Level:s Level:0)"),
+    ("Nat:zero", "Nat"),
+    ("Nat:succ", "∀ n: Nat, Nat"),
+    ("Nat:add", "∀ a: Nat, ∀ b: Nat, Nat"),
+    ("Nat:sub", "∀ a: Nat, ∀ b: Nat, Nat"),
+    ("Nat:mul", "∀ a: Nat, ∀ b: Nat, Nat"),
+    ("Nat:div", "∀ a: Nat, ∀ b: Nat, Nat"),
+    ("Nat:mod", "∀ a: Nat, ∀ b: Nat, Nat"),
+    ("Nat:beq", "∀ a: Nat, ∀ b: Nat, Bool"),
+    ("Nat:ble", "∀ a: Nat, ∀ b: Nat, Bool"),
+    (
+        "Eq",
+        "∀ u: Level, ∀ α: Sort u, ∀ a: α, ∀ b: α, Sort Level:0",
+    ),
+    (
+        "Quot",
+        "∀ u: Level, ∀ α: Sort u, ∀ r: (∀ a: α, ∀ b: α, Sort Level:0), Sort u",
+    ),
+    (
+        "Quot:mk",
+        "∀ u: Level, ∀ α: Sort u, ∀ r: (∀ a: α, ∀ b: α, Sort Level:0), ∀ a: α, Quot u α r",
+    ),
+    (
+        "Quot:lift",
+        "∀ u: Level, ∀ v: Level, ∀ α: Sort u, ∀ r: (∀ a: α, ∀ b: α, Sort Level:0), \
+        ∀ β: Sort v, ∀ f: (∀ a: α, β), ∀ h: (∀ a: α, ∀ b: α, ∀ p: r a b, Eq v β (f a) (f b)), \
+        ∀ q: Quot u α r, β",
+    ),
+    (
+        "Sigma",
+        "∀ u: Level, ∀ α: Sort u, ∀ β: (∀ a: α, Sort u), Sort u",
+    ),
+    (
+        "Sigma:mk",
+        "∀ u: Level, ∀ α: Sort u, ∀ β: (∀ a: α, Sort u), ∀ a: α, ∀ b: β a, Sigma u α β",
+    ),
+    (
+        "Sigma:fst",
+        "∀ u: Level, ∀ α: Sort u, ∀ β: (∀ a: α, Sort u), ∀ t: Sigma u α β, α",
+    ),
+    (
+        "Sigma:snd",
+        "∀ u: Level, ∀ α: Sort u, ∀ β: (∀ a: α, Sort u), ∀ t: Sigma u α β, \
+        β (Sigma:fst u α β t)",
+    ),
+];
+
+mod term;
+
+mod check;
+
+#[cfg(test)]
+mod tests;
+
+use crate::check::Context;
+use crate::term::Fresh;
+use crate::term::Term;
+use std::collections::BTreeMap;
+use std::rc::Rc;
@@ -0,0 +1,226 @@
+//! Terms with named variables, and a parser for the kernel's syntax of terms.
+
+/// A term. Bound variables are referred to by name,
+/// and the names of constants and variables never clash.
+#[derive(Clone, Debug)]
+pub(crate) enum Term {
+    Const(Rc<str>),
+    Var(Rc<str>),
+    Sortω(u16),
+    Nat(u128),
+    Lam(Rc<str>, Box<Term>, Box<Term>),
+    Pi(Rc<str>, Box<Term>, Box<Term>),
+    App(Box<Term>, Box<Term>),
+}
+
+impl Term {
+    pub(crate) fn constant(name: &str) -> Self {
+        Self::Const(name.into())
+    }
+    pub(crate) fn app(self, args: impl IntoIterator<Item = Term>) -> Self {
+        args.into_iter()
+            .fold(self, |f, a| Self::App(Box::new(f), Box::new(a)))
+    }
+    /// The head of an application and its arguments, outermost first.
+    pub(crate) fn spine(&self) -> (&Term, Vec<&Term>) {
+        let (mut head, mut args) = (self, Vec::new());
+        while let Self::App(f, a) = head {
+            args.push(&**a);
+            head = f;
+        }
+        args.reverse();
+        (head, args)
+    }
+    /// The arguments of an application of the constant `name`.
+    pub(crate) fn args_of(&self, name: &str) -> Option<Vec<&Term>> {
+        match self.spine() {
+            (Self::Const(c), args) if **c == *name => Some(args),
+            _ => None,
+        }
+    }
+    /// Whether the variable `x` occurs free.
+    pub(crate) fn occurs(&self, x: &str) -> bool {
+        match self {
+            Self::Var(y) => **y == *x,
+            Self::Const(_) | Self::Sortω(_) | Self::Nat(_) => false,
+            Self::Lam(y, a, b) | Self::Pi(y, a, b) => a.occurs(x) || (**y != *x && b.occurs(x)),
+            Self::App(f, a) => f.occurs(x) || a.occurs(x),
+        }
+    }
+    /// Replaces the free occurrences of `x` with `value`,
+    /// renaming the binders that would capture a variable of `value`.
+    pub(crate) fn subst(&self, x: &str, value: &Term, fresh: &Fresh) -> Term {
+        match self {
+            Self::Var(y) if **y == *x => value.clone(),
+            Self::Const(_) | Self::Var(_) | Self::Sortω(_) | Self::Nat(_) => self.clone(),
+            Self::App(f, a) => Self::App(
+                Box::new(f.subst(x, value, fresh)),
+                Box::new(a.subst(x, value, fresh)),
+            ),
+            Self::Lam(y, a, b) | Self::Pi(y, a, b) => {
+                let a = Box::new(a.subst(x, value, fresh));
+                let (y, b) = if **y == *x {
+                    (y.clone(), b.clone())
+                } else if value.occurs(y) && b.occurs(x) {
+                    let z = fresh.name(y);
+                    let b = b.subst(y, &Self::Var(z.clone()), fresh);
+                    (z, Box::new(b.subst(x, value, fresh)))
+                } else {
+                    (y.clone(), Box::new(b.subst(x, value, fresh)))
+                };
+                match self {
+                    Self::Lam(..) => Self::Lam(y, a, b),
+                    _ => Self::Pi(y, a, b),
+                }
+            }
+        }
+    }
+    /// Whether the terms are equal up to the names of bound variables.
+    pub(crate) fn alpha_eq(&self, other: &Term) -> bool {
+        fn go<'t>(a: &'t Term, b: &'t Term, bound: &mut Vec<(&'t str, &'t str)>) -> bool {
+            match (a, b) {
+                (Term::Const(x), Term::Const(y)) => x == y,
+                (Term::Var(x), Term::Var(y)) => {
+                    let l = bound.iter().rev().position(|&(l, _)| l == &**x);
+                    let r = bound.iter().rev().position(|&(_, r)| r == &**y);
+                    l == r && (l.is_some() || x == y)
+                }
+                (Term::Sortω(n), Term::Sortω(m)) => n == m,
+                (Term::Nat(n), Term::Nat(m)) => n == m,
+                (Term::Lam(x, a, b), Term::Lam(y, c, d))
+                | (Term::Pi(x, a, b), Term::Pi(y, c, d)) => {
+                    bound.push((x, y));
+                    let eq = go(b, d, bound);
+                    bound.pop();
+                    go(a, c, bound) && eq
+                }
+                (Term::App(f, a), Term::App(g, b)) => go(f, g, bound) && go(a, b, bound),
+                _ => false,
+            }
+        }
+        go(self, other, &mut Vec::new())
+    }
+}
+
+impl Display for Term {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Const(x) | Self::Var(x) => write!(f, "{x}"),
+            Self::Sortω(n) => write!(f, "Sortw{n}"),
+            Self::Nat(n) => write!(f, "{n}"),
+            Self::Lam(x, a, b) => write!(f, "(λ {x}: {a}, {b})"),
+            Self::Pi(x, a, b) => write!(f, "(∀ {x}: {a}, {b})"),
+            Self::App(a, b) => write!(f, "({a} {b})"),
+        }
+    }
+}
+
+/// A supply of variable names that are not used anywhere else.
+#[derive(Default)]
+pub(crate) struct Fresh(Cell<u32>);
+
+impl Fresh {
+    /// A new name based on `x`. Tokens never contain spaces, so it clashes with no source name.
+    pub(crate) fn name(&self, x: &str) -> Rc<str> {
+        let base = x.split(' ').next().unwrap();
+        self.0.set(self.0.get() + 1);
+        format!("{base} {}", self.0.get()).into()
+    }
+}
+
+/// Parses a term whose constants are those for which `declared` holds.
+/// Parsing stops before a `)`, `,` or `:=` that does not belong to the term.
+pub(crate) fn parse(input: &mut &str, declared: &dyn Fn(&str) -> bool) -> Result<Term, String> {
+    term(input, declared, &mut Vec::new())
+}
+
+fn term<'i>(
+    input: &mut &'i str,
+    declared: &dyn Fn(&str) -> bool,
+    locals: &mut Vec<&'i str>,
+) -> Result<Term, String> {
+    let mut acc: Option<Term> = None;
+    loop {
+        let t = match token(input).ok_or("unexpected end of input")? {
+            t if t.starts_with("Sortω") => Term::Sortω(number(&t["Sortω".len()..], '₀')?),
+            t if t.starts_with("Sortw") => Term::Sortω(number(&t["Sortw".len()..], '0')?),
+            binder @ ("∀" | "forall" | "λ" | "fun") => {
+                let x = token(input).ok_or("unexpected end of input")?;
+                let x = x.strip_suffix(':').ok_or("no trailing colon")?;
+                let a = term(input, declared, locals)?;
+                expect(input, ",")?;
+                locals.push(x);
+                let b = term(input, declared, locals);
+                locals.pop();
+                let (x, a, b) = (x.into(), Box::new(a), Box::new(b?));
+                match binder {
+                    "∀" | "forall" => Term::Pi(x, a, b),
+                    _ => Term::Lam(x, a, b),
+                }
+            }
+            "(" => {
+                let t = term(input, declared, locals)?;
+                expect(input, ")")?;
+                t
+            }
+            n if n.starts_with(|c: char| c.is_ascii_digit()) => Term::Nat(
+                n.parse()
+                    .map_err(|_| format!("unsupported literal `{n}`"))?,
+            ),
+            x if locals.contains(&x) => Term::Var(x.into()),
+            x if declared(x) => Term::constant(x),
+            x => return Err(format!("unknown name `{x}`")),
+        };
+        let t = match acc {
+            Some(f) => f.app([t]),
+            None => t,
+        };
+        if matches!(peek(input), None | Some(")" | "," | ";" | ":=")) {
+            return Ok(t);
+        }
+        acc = Some(t);
+    }
+}
+
+/// Digits start at `zero`, so that each token uses one spelling.
+fn number(digits: &str, zero: char) -> Result<u16, String> {
+    digits.chars().try_fold(0_u16, |n, c| {
+        let d = u32::from(c).wrapping_sub(u32::from(zero));
+        if 10 <= d {
+            return Err(format!("unexpected digit {c}"));
+        }
+        u16::try_from(u32::from(n) * 10 + d).map_err(|_| "number too large".to_owned())
+    })
+}
+
+pub(crate) fn expect(input: &mut &str, expected: &str) -> Result<(), String> {
+    match token(input) {
+        Some(t) if t == expected => Ok(()),
+        Some(t) => Err(format!("unexpected `{t}`; expected `{expected}`")),
+        None => Err(format!("unexpected end of input; expected `{expected}`")),
+    }
+}
+
+pub(crate) fn peek(mut input: &str) -> Option<&str> {
+    token(&mut input)
+}
+
+/// The next token: one of `(`, `)`, `,` and `;`, or a run of other non-whitespace characters.
+pub(crate) fn token<'i>(input: &mut &'i str) -> Option<&'i str> {
+    *input = input.trim_start();
+    let len = match input.find(|c: char| c.is_whitespace() || "(),;".contains(c)) {
+        Some(0) => 1,
+        Some(n) => n,
+        None if input.is_empty() => return None,
+        None => input.len(),
+    };
+    let token;
+    (token, *input) = input.split_at(len);
+    Some(token)
+}
+
+use std::cell::Cell;
+use std::fmt;
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::rc::Rc;
@@ -0,0 +1,287 @@
+//! Differential tests: the kernel and the reference checker must agree on every input.
+
+/// Both checkers, with the kernel's axioms.
+fn checkers() -> (Kernel, Checker) {
+    let mut reference = Checker::new();
+    for axiom in AXIOMS.lines() {
+        if let Err(e) = reference.axiom(axiom) {
+            panic!("the reference rejects `{axiom}`: {e}");
+        }
+    }
+    (Kernel::new(), reference)
+}
+
+/// Whether `term` is well-typed, failing if the checkers disagree.
+fn check(kernel: &mut Kernel, reference: &Checker, term: &str) -> bool {
+    let k = kernel.add(&format!("check {term};"));
+    let r = reference.check(term);
+    if k.is_ok() != r.is_ok() {
+        panic!("disagreement on `{term}`:\n   kernel: {k:?}\nreference: {r:?}");
+    }
+    r.is_ok()
+}
+
+/// Whether `lhs` and `rhs` are well-typed and definitionally equal,
+/// failing if the checkers disagree.
+fn equal(kernel: &mut Kernel, reference: &Checker, lhs: &str, rhs: &str) -> Option<bool> {
+    let k = kernel.add(&format!("defeq {lhs}, {rhs};"));
+    let k = k.map(|output| !output[0].contains('≢'));
+    let r = reference.equal(lhs, rhs);
+    if k.as_ref().ok() != r.as_ref().ok() {
+        panic!("disagreement on `{lhs}` ≡ `{rhs}`:\n   kernel: {k:?}\nreference: {r:?}");
+    }
+    r.ok()
+}
+
+#[test]
+fn axioms() {
+    let (mut kernel, reference) = checkers();
+    for axiom in AXIOMS.lines() {
+        let name = axiom.split(": ").next().unwrap();
+        assert!(check(&mut kernel, &reference, name));
+    }
+}
+
+#[test]
+fn levels() {
+    let (mut kernel, reference) = checkers();
+    let classes: &[&[&str]] = &[
+        &["Level:0", "Level:imax u Level:0"],
+        &[
+            "u",
+            "Level:imax u u",
+            "Level:imax Level:0 u",
+            "Level:max Level:0 u",
+            "Level:max u Level:0",
+            "Level:max u u",
+            "Level:imax (Level:s Level:0) u",
+        ],
+        &["v"],
+        &["Level:imax u (Level:s v)", "Level:max u (Level:s v)"],
+        &[
+            "Level:imax u (Level:max v w)",
+            "Level:max (Level:imax u v) (Level:imax u w)",
+        ],
+        &[
+            "Level:imax u (Level:imax v w)",
+            "Level:max (Level:imax u w) (Level:imax v w)",
+            "Level:imax (Level:max u v) w",
+        ],
+        &[
+            "Level:imax u (Level:max (Level:s Level:0) v)",
+            "Level:max (Level:s Level:0) (Level:max u v)",
+        ],
+        &["Level:max (Level:max u v) w", "Level:max u (Level:max v w)"],
+        &[
+            "Level:max u v",
+            "Level:max v u",
+            "Level:max (Level:imax u v) (Level:imax v u)",
+            "Level:max (Level:imax u v) u",
+            "Level:imax u (Level:max u v)",
+            "Level:imax (Level:max (Level:s Level:0) (Level:max u v)) (Level:max u v)",
+        ],
+        &["Level:max (Level:imax u v) v", "Level:imax u v"],
+        &[
+            "Level:s (Level:s (Level:max u v))",
+            "Level:s (Level:max (Level:s v) (Level:s u))",
+            "Level:max (Level:s (Level:s v)) (Level:s (Level:s u))",
+        ],
+        &[
+            "Level:max u (Level:s u)",
+            "Level:s u",
+            "Level:imax (Level:s u) (Level:imax u (Level:s Level:0))",
+        ],
+        &[
+            "(λ x: Level, (λ x: Level, x) w) u",
+            "(λ x: Level, x) w",
+            "w",
+        ],
+    ];
+    let levels = classes.iter().enumerate();
+    let levels = levels.flat_map(|(i, class)| class.iter().map(move |l| (i, l)));
+    for (i, l) in levels.clone() {
+        for (j, r) in levels.clone() {
+            let term = format!(
+                "λ u: Level, λ v: Level, λ w: Level, \
+                    (λ x: Sort (Level:s ({l})), x) (Sort ({r}))"
+            );
+            assert_eq!(check(&mut kernel, &reference, &term), i == j, "{l} ≡ {r}");
+        }
+    }
+}
+
+#[test]
+fn corpus() {
+    let (mut kernel, reference) = checkers();
+    let terms = [
+        ("Eq:refl (Level:s Level:0) Bool true", true),
+        ("Eq:refl (Level:s Level:0) Bool true false", false),
+        ("λ u: Level, λ α: Sort u, λ a: α, Eq:refl u α a", true),
+        ("λ u: Level, λ α: Sort u, λ a: α, Eq:refl α u a", false),
+        ("(λ α: Sort (Level:s Level:0), α) Bool", true),
+        ("(λ α: Sort Level:0, α) Bool", false),
+        ("(λ b: Bool, b) Nat:zero", false),
+        ("Bool true", false),
+        ("λ x: true, x", false),
+        ("λ u: Level, λ x: Sort u, x", true),
+        ("λ u: Level, λ x: u, x", false),
+        ("∀ u: Level, Sort u", true),
+        ("λ α: (∀ u: Level, Sort u), α", true),
+        ("(λ α: Sortw0, α) (∀ u: Level, Sort u)", true),
+        ("(λ α: Sortw0, α) Sortw0", false),
+        ("(λ α: Sortw1, α) Sortw0", true),
+        ("Sortw65535", false),
+        ("(λ x: Nat, x) (Nat:add 2 3)", true),
+        ("(λ x: Nat, x) (Nat:beq 2 3)", false),
+        ("(λ b: Bool, b) (Nat:ble 2 3)", true),
+        (
+            "(λ h: Eq (Level:s Level:0) Nat 5 (Nat:add 2 3), h) (Eq:refl (Level:s Level:0) Nat 5)",
+            true,
+        ),
+        (
+            "(λ h: Eq (Level:s Level:0) Nat 4 (Nat:add 2 3), h) (Eq:refl (Level:s Level:0) Nat 4)",
+            false,
+        ),
+        (
+            "(λ h: Eq (Level:s Level:0) Nat (Nat:succ 6) 7, h) \
+                (Eq:refl (Level:s Level:0) Nat (Nat:succ (Nat:succ 5)))",
+            true,
+        ),
+        (
+            "(λ h: Eq (Level:s Level:0) Bool true (Nat:beq (Nat:div 7 0) (Nat:mod 0 5)), h) \
+                (Eq:refl (Level:s Level:0) Bool true)",
+            true,
+        ),
+        (
+            "λ n: Nat, (λ h: Eq (Level:s Level:0) Nat (Nat:succ n) (Nat:add n 1), h) \
+                (Eq:refl (Level:s Level:0) Nat (Nat:succ n))",
+            false,
+        ),
+        // Proof irrelevance.
+        (
+            "λ P: Sort Level:0, λ p: P, λ q: P, \
+                (λ h: Eq Level:0 P p q, h) (Eq:refl Level:0 P p)",
+            true,
+        ),
+        (
+            "λ P: Sort (Level:s Level:0), λ p: P, λ q: P, \
+                (λ h: Eq (Level:s Level:0) P p q, h) (Eq:refl (Level:s Level:0) P p)",
+            false,
+        ),
+        // `Quot:lift` computes on `Quot:mk`.
+        (
+            "(λ h: Eq (Level:s Level:0) Bool true \
+                (Quot:lift (Level:s Level:0) (Level:s Level:0) Bool \
+                    (Eq (Level:s Level:0) Bool) Bool (λ b: Bool, b) \
+                    (λ a: Bool, λ b: Bool, λ p: Eq (Level:s Level:0) Bool a b, \
+                        Eq:elim (Level:s Level:0) Level:0 Bool \
+                            (λ c: Bool, Eq (Level:s Level:0) Bool a c) a \
+                            (Eq:refl (Level:s Level:0) Bool a) b p) \
+                    (Quot:mk (Level:s Level:0) Bool (Eq (Level:s Level:0) Bool) true)), h) \
+                (Eq:refl (Level:s Level:0) Bool true)",
+            true,
+        ),
+        // Projections compute on pairs, and pairs have η.
+        (
+            "(λ h: Eq (Level:s Level:0) Nat 2 \
+                (Sigma:snd (Level:s Level:0) Nat (λ n: Nat, Nat) \
+                    (Sigma:mk (Level:s Level:0) Nat (λ n: Nat, Nat) 1 2)), h) \
+                (Eq:refl (Level:s Level:0) Nat 2)",
+            true,
+        ),
+        (
+            "λ t: Sigma (Level:s Level:0) Nat (λ n: Nat, Nat), \
+                (λ h: Eq (Level:s Level:0) (Sigma (Level:s Level:0) Nat (λ n: Nat, Nat)) t \
+                    (Sigma:mk (Level:s Level:0) Nat (λ n: Nat, Nat) \
+                        (Sigma:fst (Level:s Level:0) Nat (λ n: Nat, Nat) t) \
+                        (Sigma:snd (Level:s Level:0) Nat (λ n: Nat, Nat) t)), h) \
+                (Eq:refl (Level:s Level:0) (Sigma (Level:s Level:0) Nat (λ n: Nat, Nat)) t)",
+            true,
+        ),
+        (
+            "λ t: Sigma (Level:s Level:0) Nat (λ n: Nat, Nat), \
+                (λ h: Eq (Level:s Level:0) (Sigma (Level:s Level:0) Nat (λ n: Nat, Nat)) t \
+                    (Sigma:mk (Level:s Level:0) Nat (λ n: Nat, Nat) \
+                        (Sigma:snd (Level:s Level:0) Nat (λ n: Nat, Nat) t) \
+                        (Sigma:fst (Level:s Level:0) Nat (λ n: Nat, Nat) t)), h) \
+                (Eq:refl (Level:s Level:0) (Sigma (Level:s Level:0) Nat (λ n: Nat, Nat)) t)",
+            false,
+        ),
+        // Capture-avoiding substitution.
+        (
+            "λ α: Sort (Level:s Level:0), λ a: α, \
+                (λ f: (∀ b: α, ∀ a: Bool, α), f a true) (λ b: α, λ a: Bool, b)",
+            true,
+        ),
+    ];
+    for (term, well_typed) in terms {
+        assert_eq!(check(&mut kernel, &reference, term), well_typed, "{term}");
+    }
+}
+
+#[test]
+fn equalities() {
+    let (mut kernel, reference) = checkers();
+    let pairs = [
+        ("Nat:add 2 2", "4", Some(true)),
+        ("Nat:succ (Nat:succ Nat:zero)", "2", Some(true)),
+        ("Nat:sub 2 5", "Nat:zero", Some(true)),
+        ("Nat:mul 3 4", "13", Some(false)),
+        ("Nat:beq 3 3", "true", Some(true)),
+        ("Nat:ble 4 3", "true", Some(false)),
+        ("λ n: Nat, Nat:succ n", "λ m: Nat, Nat:succ m", Some(true)),
+        ("λ n: Nat, Nat:succ n", "Nat:succ", Some(false)),
+        ("∀ a: Bool, Bool", "∀ b: Bool, Bool", Some(true)),
+        (
+            "Sort (Level:max Level:0 (Level:s Level:0))",
+            "Bool",
+            Some(false),
+        ),
+        (
+            "Sort (Level:max Level:0 (Level:s Level:0))",
+            "Sort (Level:s Level:0)",
+            Some(true),
+        ),
+        ("Sortw0", "Sortw1", Some(false)),
+        ("Bool", "true", Some(false)),
+        ("Nat:add true 1", "1", None),
+    ];
+    for (lhs, rhs, expected) in pairs {
+        assert_eq!(
+            equal(&mut kernel, &reference, lhs, rhs),
+            expected,
+            "{lhs} ≡ {rhs}"
+        );
+    }
+}
+
+#[test]
+fn definitions() {
+    let (mut kernel, mut reference) = checkers();
+    let defs = [
+        (
+            "id: ∀ u: Level, ∀ α: Sort u, ∀ a: α, α := λ u: Level, λ α: Sort u, λ a: α, a",
+            true,
+        ),
+        ("two: Nat := Nat:add 1 1", true),
+        ("three: Nat := true", false),
+        ("four: id (Level:s Level:0) Nat two := 2", false),
+        (
+            "five: Eq (Level:s Level:0) Nat two 2 := Eq:refl (Level:s Level:0) Nat 2",
+            false,
+        ),
+        ("id: Nat := 0", false),
+    ];
+    for (def, accepted) in defs {
+        let k = kernel.add(&format!("def {def};"));
+        let r = reference.define(def);
+        if k.is_ok() != r.is_ok() {
+            panic!("disagreement on `{def}`:\n   kernel: {k:?}\nreference: {r:?}");
+        }
+        assert_eq!(r.is_ok(), accepted, "{def}");
+    }
+}
+
+use crate::Checker;
+use kernel::Kernel;
+use kernel::AXIOMS;